@@ -10,6 +10,8 @@ use std::thread::{self, JoinHandle};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tauri::State;
 
+use crate::workspace::WorkspaceRegistry;
+
 const SHADOW_BRANCH: &str = "autogit/tracking";
 const SHADOW_REF: &str = "refs/heads/autogit/tracking";
 const AUTOGIT_INDEX_PATH: &str = ".git/autogit-index";
@@ -23,6 +25,17 @@ const LOCK_FILE_RETRY_SLEEP_SECS: u64 = 2;
 const COMMIT_MAX_RETRIES: u32 = 3;
 const COMMIT_RETRY_SLEEP_SECS: u64 = 10;
 
+/// Directory names never tracked by autogit nor scanned for skills.
+pub(crate) const EXCLUDED_DIR_NAMES: [&str; 7] = [
+    ".git",
+    "node_modules",
+    "target",
+    "dist",
+    "build",
+    ".next",
+    "__pycache__",
+];
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AutogitConfig {
     pub interval_seconds: u64,
@@ -80,8 +93,10 @@ pub struct AutogitDaemonManager {
 #[tauri::command]
 pub async fn start_autogit_daemon(
     state: State<'_, AutogitDaemonManager>,
+    workspaces: State<'_, WorkspaceRegistry>,
     watch_path: String,
 ) -> Result<AutogitDaemonStatus, String> {
+    workspaces.resolve(&watch_path)?;
     let normalized_watch_path = normalize_watch_path(&watch_path)?;
     let repo_root = find_git_root(&normalized_watch_path).ok_or_else(|| {
         format!(
@@ -186,8 +201,11 @@ pub async fn autogit_daemon_status(
 ///
 /// If no config file exists a default is created and returned.
 #[tauri::command]
-pub async fn get_autogit_config(repo_root: String) -> Result<AutogitConfig, String> {
-    load_or_create_config(&PathBuf::from(repo_root))
+pub async fn get_autogit_config(
+    workspaces: State<'_, WorkspaceRegistry>,
+    repo_root: String,
+) -> Result<AutogitConfig, String> {
+    load_or_create_config(&workspaces.resolve(&repo_root)?)
 }
 
 /// Persist a new `.autogit.json` for a repo.
@@ -197,10 +215,11 @@ pub async fn get_autogit_config(repo_root: String) -> Result<AutogitConfig, Stri
 /// A change to `interval_seconds` takes effect on the next daemon start.
 #[tauri::command]
 pub async fn set_autogit_config(
+    workspaces: State<'_, WorkspaceRegistry>,
     repo_root: String,
     config: AutogitConfig,
 ) -> Result<AutogitConfig, String> {
-    save_autogit_config(&PathBuf::from(repo_root), &config)?;
+    save_autogit_config(&workspaces.resolve(&repo_root)?, &config)?;
     Ok(config)
 }
 
@@ -252,7 +271,7 @@ fn normalize_watch_path(raw: &str) -> Result<PathBuf, String> {
     std::fs::canonicalize(directory).map_err(|error| error.to_string())
 }
 
-fn find_git_root(start: &Path) -> Option<PathBuf> {
+pub(crate) fn find_git_root(start: &Path) -> Option<PathBuf> {
     let mut cursor = Some(start.to_path_buf());
     while let Some(path) = cursor {
         if path.join(".git").exists() {
@@ -417,19 +436,9 @@ fn events_touch_config_file(repo_root: &Path, events: &[DebouncedEvent]) -> bool
 }
 
 fn should_exclude_path(path: &Path, config_exclude: &[String]) -> bool {
-    let component_blacklist = [
-        ".git",
-        "node_modules",
-        "target",
-        "dist",
-        "build",
-        ".next",
-        "__pycache__",
-    ];
-
     if path.components().any(|component| {
         if let Component::Normal(value) = component {
-            component_blacklist.iter().any(|blocked| value == *blocked)
+            EXCLUDED_DIR_NAMES.iter().any(|blocked| value == *blocked)
         } else {
             false
         }
@@ -507,13 +516,10 @@ fn commit_with_retry(
 ) -> Result<Option<String>, String> {
     // Respect any existing lock file before touching the index.
     let lock_path = repo_root.join(".git").join("index.lock");
-    if lock_path.exists() {
-        if !wait_for_git_lock(repo_root) {
-            let msg =
-                "git index.lock persists after retries; skipping this commit batch".to_string();
-            log_autogit_error(repo_root, &msg);
-            return Err(msg);
-        }
+    if lock_path.exists() && !wait_for_git_lock(repo_root) {
+        let msg = "git index.lock persists after retries; skipping this commit batch".to_string();
+        log_autogit_error(repo_root, &msg);
+        return Err(msg);
     }
 
     let mut last_err = String::new();
@@ -791,9 +797,14 @@ pub struct GitRepoInfo {
 /// - Submodules: returns the *innermost* `.git` (nearest to entry_path).
 /// - No-git: returns `is_git_repo: false`, both `Option` fields `None`.
 /// - File paths: treated as their parent directory.
+///
+/// `entry_path` must be inside an open workspace.
 #[tauri::command]
-pub fn detect_git_repo(entry_path: String) -> GitRepoInfo {
-    let raw = PathBuf::from(&entry_path);
+pub fn detect_git_repo(
+    workspaces: State<'_, WorkspaceRegistry>,
+    entry_path: String,
+) -> Result<GitRepoInfo, String> {
+    let raw = workspaces.resolve(&entry_path)?;
     // If the path is a file, start the walk from its parent dir.
     let start = if raw.is_file() {
         raw.parent().map(|p| p.to_path_buf()).unwrap_or(raw.clone())
@@ -801,7 +812,7 @@ pub fn detect_git_repo(entry_path: String) -> GitRepoInfo {
         raw.clone()
     };
 
-    match find_git_root(&start) {
+    Ok(match find_git_root(&start) {
         Some(root) => {
             // Make entry_path relative to root; fall back to empty string if
             // it's exactly equal to the root.
@@ -821,5 +832,5 @@ pub fn detect_git_repo(entry_path: String) -> GitRepoInfo {
             repo_root: None,
             entry_relative_path: None,
         },
-    }
+    })
 }
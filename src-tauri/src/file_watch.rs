@@ -7,7 +7,10 @@ use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
 use std::sync::Mutex;
 use std::thread::{self, JoinHandle};
 use std::time::Duration;
-use tauri::{Emitter, State, Window};
+use tauri::{Emitter, Manager, State, Window};
+
+use crate::search_index::SearchManager;
+use crate::workspace::WorkspaceRegistry;
 
 const WATCH_DEBOUNCE_SECONDS: u64 = 2;
 
@@ -31,8 +34,10 @@ pub struct DirectoryWatcherManager {
 pub async fn watch_directory(
     window: Window,
     state: State<'_, DirectoryWatcherManager>,
+    workspaces: State<'_, WorkspaceRegistry>,
     path: String,
 ) -> Result<String, String> {
+    workspaces.resolve(&path)?;
     let watch_path = normalize_watch_path(&path)?;
     stop_watcher_for_label(&state, window.label());
 
@@ -127,6 +132,9 @@ fn run_directory_watcher(window: Window, watch_path: PathBuf, stop_rx: Receiver<
         match event_rx.recv_timeout(Duration::from_millis(500)) {
             Ok(Ok(events)) => {
                 let payloads = collect_changed_payloads(events);
+                let changed: Vec<PathBuf> =
+                    payloads.iter().map(|p| PathBuf::from(&p.path)).collect();
+                window.state::<SearchManager>().mark_changed(&changed);
                 for payload in payloads {
                     if window.emit("file-changed", payload).is_err() {
                         return;
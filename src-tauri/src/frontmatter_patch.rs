@@ -0,0 +1,519 @@
+//! frontmatter_patch.rs — Edit single frontmatter keys in place.
+//!
+//! `update_frontmatter` applies set/remove/append operations to the top-level
+//! keys of a skill's YAML frontmatter by editing the text itself, instead of
+//! round-tripping through `serde_yaml`. Lines that belong to other keys,
+//! comments, key order, quoting style and the body stay byte-identical, so a
+//! status toggle produces a one-line diff on the autogit shadow branch.
+//!
+//! The result is re-parsed before it is written; an edit that would leave
+//! invalid YAML is refused rather than saved.
+
+use serde::Deserialize;
+use serde_json::Value;
+use tauri::State;
+
+use crate::scan_cache::mtime_millis;
+use crate::skill_write::{check_snapshot, write_atomic, WriteExpectation, WrittenFile};
+use crate::workspace::WorkspaceRegistry;
+
+// ---------------------------------------------------------------------------
+// Public types
+// ---------------------------------------------------------------------------
+
+/// One edit to a top-level frontmatter key.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum FrontmatterOp {
+    /// Replace the value of `key`, adding the key at the end if missing.
+    Set { key: String, value: Value },
+    /// Delete `key` and its value. Missing keys are ignored.
+    Remove { key: String },
+    /// Add `value` to the list under `key` (creating it if missing) unless
+    /// it is already present.
+    Append { key: String, value: Value },
+}
+
+// ---------------------------------------------------------------------------
+// YAML text model
+// ---------------------------------------------------------------------------
+
+/// A top-level key and the lines holding its value, as indices into the
+/// block's lines (`end` exclusive).
+struct Entry {
+    start: usize,
+    end: usize,
+    /// Byte offset in the key line just past the `:`.
+    value_start: usize,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum QuoteStyle {
+    Plain,
+    Single,
+    Double,
+}
+
+/// Parse `line` as `key: ...` at column 0, returning the key and the offset
+/// just past the colon.
+fn parse_key_line(line: &str) -> Option<(String, usize)> {
+    let first = line.chars().next()?;
+    if first.is_whitespace() || matches!(first, '#' | '-' | '\r') {
+        return None;
+    }
+
+    if first == '"' || first == '\'' {
+        let close = line[1..].find(first)? + 1;
+        let rest = &line[close + 1..];
+        let colon = rest.len() - rest.trim_start().len();
+        return rest
+            .trim_start()
+            .starts_with(':')
+            .then(|| (line[1..close].to_string(), close + 1 + colon + 1));
+    }
+
+    let bytes = line.as_bytes();
+    (0..bytes.len())
+        .find(|&i| {
+            bytes[i] == b':'
+                && bytes
+                    .get(i + 1)
+                    .is_none_or(|next| next.is_ascii_whitespace())
+        })
+        .map(|i| (line[..i].trim_end().to_string(), i + 1))
+}
+
+fn top_level_entries(lines: &[&str]) -> Vec<(String, Entry)> {
+    let mut entries: Vec<(String, Entry)> = Vec::new();
+
+    for (index, line) in lines.iter().enumerate() {
+        if let Some((key, value_start)) = parse_key_line(line) {
+            entries.push((
+                key,
+                Entry {
+                    start: index,
+                    end: index + 1,
+                    value_start,
+                },
+            ));
+            continue;
+        }
+
+        // Indented lines, column-0 `- item` lines and the rest of a flow
+        // collection continue the current value. Blank lines and column-0
+        // comments are left between entries so edits never swallow them.
+        let detached = line.trim().is_empty() || line.starts_with('#');
+        if !detached {
+            if let Some((_, entry)) = entries.last_mut() {
+                entry.end = index + 1;
+            }
+        }
+    }
+
+    entries
+}
+
+/// Split a single-line value into the value text and a trailing ` # comment`
+/// (including its leading whitespace), ignoring `#` inside quotes.
+fn split_comment(value: &str) -> (&str, &str) {
+    let bytes = value.as_bytes();
+    let mut quote: Option<u8> = None;
+    for i in 0..bytes.len() {
+        match (quote, bytes[i]) {
+            (None, b'"' | b'\'') => quote = Some(bytes[i]),
+            (Some(q), b) if b == q => quote = None,
+            (None, b'#') if i == 0 || bytes[i - 1].is_ascii_whitespace() => {
+                let text = value[..i].trim_end();
+                return (text, &value[text.len()..]);
+            }
+            _ => {}
+        }
+    }
+    (value, "")
+}
+
+fn quote_style_of(text: &str) -> QuoteStyle {
+    match text
+        .trim_start()
+        .trim_start_matches(['[', '-', ' '])
+        .chars()
+        .next()
+    {
+        Some('"') => QuoteStyle::Double,
+        Some('\'') => QuoteStyle::Single,
+        _ => QuoteStyle::Plain,
+    }
+}
+
+/// A string that reads back as the same string when written unquoted.
+fn is_plain_safe(text: &str) -> bool {
+    let starts_ok = text
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_alphanumeric() || matches!(c, '_' | '.' | '/'));
+    starts_ok
+        && text
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, ' ' | '_' | '-' | '.' | '/' | '@' | '+'))
+        && !text.ends_with(' ')
+        && matches!(
+            serde_yaml::from_str::<serde_yaml::Value>(text),
+            Ok(serde_yaml::Value::String(ref parsed)) if parsed == text
+        )
+}
+
+pub(crate) fn render_scalar(value: &Value, style: QuoteStyle) -> Option<String> {
+    Some(match value {
+        Value::Null => "null".to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => match style {
+            QuoteStyle::Plain if is_plain_safe(s) => s.clone(),
+            QuoteStyle::Single if !s.contains('\n') => format!("'{}'", s.replace('\'', "''")),
+            // A JSON string literal is a valid YAML double-quoted scalar.
+            _ => serde_json::to_string(s).ok()?,
+        },
+        Value::Array(_) | Value::Object(_) => return None,
+    })
+}
+
+/// Lines for `key`'s new value, keeping the style of `existing` lines where
+/// possible: inline scalars and flow lists stay on the key line, block
+/// lists keep their item prefix, nested maps use block style.
+fn render_entry(
+    key_prefix: &str,
+    value: &Value,
+    existing: &[&str],
+    eol: &str,
+) -> Result<String, String> {
+    let (inline_old, comment) = existing
+        .first()
+        .map(|line| split_comment(line[key_prefix.len()..].trim_end_matches(['\r', '\n'])))
+        .unwrap_or(("", ""));
+    let block_item = existing
+        .iter()
+        .skip(1)
+        .find(|line| line.trim_start().starts_with('-'))
+        .copied();
+    let style_source = block_item.unwrap_or(inline_old);
+    let style = if existing.is_empty() {
+        QuoteStyle::Plain
+    } else {
+        quote_style_of(style_source)
+    };
+
+    if let Some(scalar) = render_scalar(value, style) {
+        return Ok(format!("{} {}{}{}", key_prefix, scalar, comment, eol));
+    }
+
+    if let Value::Array(items) = value {
+        let scalars: Option<Vec<String>> = items
+            .iter()
+            .map(|item| render_scalar(item, style))
+            .collect();
+        if let Some(scalars) = scalars {
+            if let (Some(item_line), true) = (block_item, inline_old.trim().is_empty()) {
+                let prefix_len = item_line.len() - item_line.trim_start().len();
+                let prefix = format!("{}- ", &item_line[..prefix_len]);
+                let mut out = format!("{}{}{}", key_prefix, comment, eol);
+                for scalar in scalars {
+                    out.push_str(&format!("{}{}{}", prefix, scalar, eol));
+                }
+                return Ok(out);
+            }
+            return Ok(format!(
+                "{} [{}]{}{}",
+                key_prefix,
+                scalars.join(", "),
+                comment,
+                eol
+            ));
+        }
+    }
+
+    let nested = serde_yaml::to_string(value).map_err(|e| format!("Cannot render value: {}", e))?;
+    let mut out = format!("{}{}{}", key_prefix, comment, eol);
+    for line in nested.lines() {
+        out.push_str(&format!("  {}{}", line, eol));
+    }
+    Ok(out)
+}
+
+// ---------------------------------------------------------------------------
+// Applying operations
+// ---------------------------------------------------------------------------
+
+fn format_key(key: &str) -> String {
+    if is_plain_safe(key) && !key.contains(' ') {
+        key.to_string()
+    } else {
+        serde_json::to_string(key).unwrap_or_else(|_| key.to_string())
+    }
+}
+
+fn parse_block(block: &str) -> Result<serde_json::Map<String, Value>, String> {
+    if block.trim().is_empty() {
+        return Ok(serde_json::Map::new());
+    }
+    match serde_yaml::from_str::<serde_yaml::Value>(block) {
+        Ok(yaml) => match serde_json::to_value(yaml) {
+            Ok(Value::Object(map)) => Ok(map),
+            Ok(Value::Null) => Ok(serde_json::Map::new()),
+            _ => Err("Frontmatter is not a key/value mapping".to_string()),
+        },
+        Err(error) => Err(format!("Frontmatter is not valid YAML: {}", error)),
+    }
+}
+
+/// Try to add `item` to a list in place (new block item line or before the
+/// closing `]` of a one-line flow list). `None` means the list must be
+/// re-rendered.
+fn append_in_place(lines: &[&str], entry: &Entry, item: &Value, eol: &str) -> Option<Vec<String>> {
+    let key_line = lines[entry.start];
+    let (inline, comment) =
+        split_comment(key_line[entry.value_start..].trim_end_matches(['\r', '\n']));
+    let inline = inline.trim();
+
+    if inline.is_empty() {
+        let last_item = lines[entry.start + 1..entry.end]
+            .iter()
+            .rev()
+            .find(|line| line.trim_start().starts_with('-'))?;
+        let prefix_len = last_item.len() - last_item.trim_start().len();
+        let scalar = render_scalar(item, quote_style_of(last_item))?;
+        let mut out: Vec<String> = lines[entry.start..entry.end]
+            .iter()
+            .map(|l| l.to_string())
+            .collect();
+        out.push(format!("{}- {}{}", &last_item[..prefix_len], scalar, eol));
+        return Some(out);
+    }
+
+    if entry.end != entry.start + 1 || !inline.starts_with('[') || !inline.ends_with(']') {
+        return None;
+    }
+    let scalar = render_scalar(item, quote_style_of(inline))?;
+    let inner = inline[1..inline.len() - 1].trim_end();
+    let list = if inner.trim().is_empty() {
+        format!("[{}]", scalar)
+    } else {
+        format!("[{}, {}]", inner, scalar)
+    };
+    Some(vec![format!(
+        "{} {}{}{}",
+        &key_line[..entry.value_start],
+        list,
+        comment,
+        eol
+    )])
+}
+
+/// Apply `op` to the YAML `block` (the text between the fences).
+fn apply_op(block: &str, op: &FrontmatterOp, eol: &str) -> Result<String, String> {
+    let current = parse_block(block)?;
+    let lines: Vec<&str> = block.split_inclusive('\n').collect();
+    let entries = top_level_entries(&lines);
+    let key = match op {
+        FrontmatterOp::Set { key, .. }
+        | FrontmatterOp::Remove { key }
+        | FrontmatterOp::Append { key, .. } => key,
+    };
+    let found = entries
+        .iter()
+        .find(|(name, _)| name == key)
+        .map(|(_, entry)| entry);
+
+    let replacement: Vec<String> = match op {
+        FrontmatterOp::Remove { .. } => Vec::new(),
+        FrontmatterOp::Set { value, .. } => {
+            let (prefix, existing) = match found {
+                Some(entry) => (
+                    lines[entry.start][..entry.value_start].to_string(),
+                    &lines[entry.start..entry.end],
+                ),
+                None => (format!("{}:", format_key(key)), &lines[0..0]),
+            };
+            vec![render_entry(&prefix, value, existing, eol)?]
+        }
+        FrontmatterOp::Append { value, .. } => {
+            let mut items = match current.get(key) {
+                None | Some(Value::Null) => Vec::new(),
+                Some(Value::Array(items)) => items.clone(),
+                Some(_) => return Err(format!("Cannot append to {}: not a list", key)),
+            };
+            if items.contains(value) {
+                return Ok(block.to_string());
+            }
+            match found.and_then(|entry| append_in_place(&lines, entry, value, eol)) {
+                Some(lines) => lines,
+                None => {
+                    items.push(value.clone());
+                    return apply_op(
+                        block,
+                        &FrontmatterOp::Set {
+                            key: key.clone(),
+                            value: Value::Array(items),
+                        },
+                        eol,
+                    );
+                }
+            }
+        }
+    };
+
+    let (start, end) = match found {
+        Some(entry) => (entry.start, entry.end),
+        None if matches!(op, FrontmatterOp::Remove { .. }) => return Ok(block.to_string()),
+        None => (lines.len(), lines.len()),
+    };
+
+    let mut out = String::with_capacity(block.len() + 64);
+    for line in &lines[..start] {
+        out.push_str(line);
+    }
+    if start == lines.len() && !out.is_empty() && !out.ends_with('\n') {
+        out.push_str(eol);
+    }
+    for line in &replacement {
+        out.push_str(line);
+    }
+    for line in &lines[end..] {
+        out.push_str(line);
+    }
+    Ok(out)
+}
+
+/// Apply `ops` to the frontmatter of `content`, creating a YAML block if the
+/// file has none. Everything outside the edited keys is kept byte for byte.
+fn patch_content(content: &str, ops: &[FrontmatterOp]) -> Result<String, String> {
+    let eol = if content.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+    let trimmed = content.trim_start();
+    let lead = content.len() - trimmed.len();
+
+    let (block_start, block_end) = if let Some(after_fence) = trimmed.strip_prefix("---") {
+        let first_eol = trimmed.find('\n').ok_or("Unterminated frontmatter")?;
+        let close = after_fence
+            .find("\n---")
+            .map(|i| i + 3 + 1)
+            .ok_or("Unterminated frontmatter")?;
+        (lead + (first_eol + 1).min(close), lead + close)
+    } else if trimmed.starts_with("+++") || trimmed.starts_with('{') {
+        return Err("Only YAML frontmatter can be patched in place".to_string());
+    } else {
+        let mut block = String::new();
+        for op in ops {
+            block = apply_op(&block, op, eol)?;
+        }
+        parse_block(&block)?;
+        return Ok(format!("---{eol}{block}---{eol}{content}"));
+    };
+
+    let mut block = content[block_start..block_end].to_string();
+    for op in ops {
+        block = apply_op(&block, op, eol)?;
+    }
+    parse_block(&block)?;
+
+    Ok(format!(
+        "{}{}{}",
+        &content[..block_start],
+        block,
+        &content[block_end..]
+    ))
+}
+
+// ---------------------------------------------------------------------------
+// Command
+// ---------------------------------------------------------------------------
+
+/// Apply `patch` to the frontmatter of an existing `.md` skill file, writing
+/// atomically. `expected_hash` guards against edits made on disk since the
+/// caller last read the file, as in `write_skill_file`.
+#[tauri::command]
+pub async fn update_frontmatter(
+    workspaces: State<'_, WorkspaceRegistry>,
+    file_path: String,
+    patch: Vec<FrontmatterOp>,
+    expected_hash: Option<String>,
+) -> Result<WrittenFile, String> {
+    let path = workspaces.resolve(&file_path)?;
+    let path = path.as_path();
+    if !path.is_file() {
+        return Err(format!("Not a file: {}", file_path));
+    }
+    if path.extension().and_then(|e| e.to_str()) != Some("md") {
+        return Err(format!(
+            "Refusing to write non-markdown file: {}",
+            file_path
+        ));
+    }
+
+    let expected = WriteExpectation {
+        hash: expected_hash,
+        mtime: None,
+    };
+    // One read serves both the conflict check and the patch, so a write in
+    // between cannot slip past the check.
+    let metadata = std::fs::metadata(path).map_err(|e| e.to_string())?;
+    let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+    check_snapshot(path, &bytes, mtime_millis(&metadata), &expected).map_err(|e| e.to_string())?;
+
+    let content = String::from_utf8(bytes).map_err(|_| format!("Not UTF-8: {}", file_path))?;
+    let patched = patch_content(&content, &patch)?;
+    write_atomic(path, patched.as_bytes()).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const SAMPLE: &str = "---\nname: data-analysis # canonical\nstatus: draft\ntags: [pandas, data]\nrelated:\n  - \"py-pandas\"\nsteps:\n  - name: load\n---\n# Body\n\nUnchanged.\n";
+
+    fn set(key: &str, value: Value) -> FrontmatterOp {
+        FrontmatterOp::Set {
+            key: key.to_string(),
+            value,
+        }
+    }
+
+    #[test]
+    fn test_patch_edits_only_target_lines() {
+        let ops = [
+            set("status", json!("stable")),
+            FrontmatterOp::Append {
+                key: "tags".to_string(),
+                value: json!("reporting"),
+            },
+            FrontmatterOp::Append {
+                key: "related".to_string(),
+                value: json!("py-seaborn"),
+            },
+            FrontmatterOp::Remove {
+                key: "steps".to_string(),
+            },
+            set("version", json!("1.0")),
+        ];
+        let patched = patch_content(SAMPLE, &ops).unwrap();
+        assert_eq!(
+            patched,
+            "---\nname: data-analysis # canonical\nstatus: stable\ntags: [pandas, data, reporting]\nrelated:\n  - \"py-pandas\"\n  - \"py-seaborn\"\nversion: \"1.0\"\n---\n# Body\n\nUnchanged.\n"
+        );
+    }
+
+    #[test]
+    fn test_patch_keeps_comment_and_handles_missing_frontmatter() {
+        let patched = patch_content(SAMPLE, &[set("name", json!("Data Analysis"))]).unwrap();
+        assert!(patched.starts_with("---\nname: Data Analysis # canonical\n"));
+
+        let plain = "# Just a body\r\n";
+        let patched = patch_content(plain, &[set("status", json!("draft"))]).unwrap();
+        assert_eq!(patched, "---\r\nstatus: draft\r\n---\r\n# Just a body\r\n");
+
+        assert!(patch_content("+++\na = 1\n+++\n", &[set("a", json!(2))]).is_err());
+    }
+}
@@ -1,13 +1,58 @@
+use ignore::overrides::OverrideBuilder;
+use ignore::WalkBuilder;
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::path::Path;
-use walkdir::WalkDir;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::ipc::Channel;
+use tauri::{Emitter, Manager, State, Window};
+
+use crate::autogit::EXCLUDED_DIR_NAMES;
+use crate::graph_builder::{assign_node_ids, IdCollision};
+use crate::scan_cache::{self, cache_key, CacheReport, CacheStatus, CachedEntry, ScanCache};
+use crate::skill_bundle::{bundle_dir_name, is_bundle_manifest, BundleLayout, SkillBundle};
+use crate::skill_document::{parse_document, SkillDocument};
+use crate::skill_templates::WORKSPACE_TEMPLATE_DIR;
+use crate::skill_write::{
+    check_expectation, write_atomic, WriteError, WriteExpectation, WrittenFile,
+};
+use crate::workspace::WorkspaceRegistry;
+
+const SCAN_PROGRESS_EVENT: &str = "scan-progress";
+// Emit a progress event every N parsed files (plus once at the end).
+const SCAN_PROGRESS_INTERVAL: usize = 64;
+// Files parsed in parallel per batch; also the streaming batch size.
+const SCAN_BATCH_SIZE: usize = 256;
+// Project-specific ignore file, read with gitignore semantics alongside
+// `.gitignore` and `.ignore`.
+const SKILLS_IGNORE_FILE: &str = ".skillsignore";
+// Markdown files above this size are reported as `too-large` and not parsed.
+pub(crate) const MAX_SKILL_FILE_BYTES: u64 = 2 * 1024 * 1024;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SkillEntry {
     pub path: String,
     pub name: String,
     pub frontmatter: Option<serde_json::Value>,
+    /// Syntax the frontmatter was written in; `None` without frontmatter.
+    #[serde(default)]
+    pub frontmatter_format: Option<FrontmatterFormat>,
     pub body: String,
+    /// Set when this entry is the `SKILL.md` of a directory bundle.
+    #[serde(default)]
+    pub bundle: Option<SkillBundle>,
+    /// Hash and mtime of the file as read, to pass back to `write_skill_file`
+    /// so edits made outside the app are detected.
+    #[serde(default)]
+    pub content_hash: Option<String>,
+    #[serde(default)]
+    pub mtime: Option<u64>,
+    /// Parsed body structure; only filled in by `read_skill_file` when
+    /// asked for.
+    #[serde(default)]
+    pub document: Option<SkillDocument>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -15,110 +60,262 @@ pub struct ScanResult {
     pub skills: Vec<SkillEntry>,
     pub skipped: usize,
     pub errors: usize,
+    /// True when the scan was stopped via `cancel_scan` before it finished.
+    pub cancelled: bool,
+    /// One entry per skipped or failed markdown file, in path order.
+    pub diagnostics: Vec<ScanDiagnostic>,
+    /// Skills whose names normalize to the same graph node id.
+    #[serde(default)]
+    pub id_collisions: Vec<IdCollision>,
 }
 
-fn extract_frontmatter(content: &str) -> (Option<serde_json::Value>, &str) {
-    let trimmed = content.trim_start();
-    if !trimmed.starts_with("---") {
-        return (None, content);
+/// Extra filters supplied by the frontend on top of the ignore files.
+///
+/// Globs use gitignore syntax and are matched relative to the scanned folder.
+/// When `include` is non-empty only matching files are scanned; `exclude`
+/// always wins. Both take precedence over `.gitignore`/`.skillsignore`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScanOptions {
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+}
+
+impl ScanOptions {
+    /// No caller filters: the scan sees every file the ignore files allow.
+    pub fn is_unfiltered(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
     }
+}
 
-    let after_first = &trimmed[3..];
-    if let Some(end_idx) = after_first.find("\n---") {
-        let yaml_str = &after_first[..end_idx];
-        let body_start = end_idx + 4; // skip "\n---"
-        let body = after_first[body_start..].trim_start_matches('\n');
+/// Reason a markdown file did not make it into the scan results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DiagnosticCode {
+    NoFrontmatter,
+    MalformedYaml,
+    MalformedToml,
+    MalformedJson,
+    IoError,
+    NonUtf8,
+    SymlinkLoop,
+    TooLarge,
+}
 
-        match serde_yaml::from_str::<serde_yaml::Value>(yaml_str) {
-            Ok(yaml_val) => {
-                let json_val = serde_json::to_value(&yaml_val).unwrap_or(serde_json::Value::Null);
-                (Some(json_val), body)
-            }
-            Err(_) => (None, content), // malformed YAML — skip frontmatter silently
+/// Per-file explanation for a skipped or failed file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanDiagnostic {
+    pub path: String,
+    pub code: DiagnosticCode,
+    /// Detail text: the I/O error, or the parser message for the
+    /// `malformed-*` codes.
+    pub message: Option<String>,
+    /// 1-based position of a frontmatter parse error within the file.
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+}
+
+impl ScanDiagnostic {
+    fn new(path: &Path, code: DiagnosticCode, message: Option<String>) -> Self {
+        Self {
+            path: path.to_string_lossy().to_string(),
+            code,
+            message,
+            line: None,
+            column: None,
         }
-    } else {
-        (None, content)
+    }
+
+    /// Problems reading the file count towards `errors`; files that are
+    /// readable but not skills count towards `skipped`.
+    fn is_error(&self) -> bool {
+        matches!(
+            self.code,
+            DiagnosticCode::IoError | DiagnosticCode::NonUtf8 | DiagnosticCode::SymlinkLoop
+        )
     }
 }
 
-fn derive_name(frontmatter: &Option<serde_json::Value>, file_stem: &str) -> String {
-    if let Some(fm) = frontmatter {
-        if let Some(name) = fm.get("name").and_then(|v| v.as_str()) {
-            if !name.trim().is_empty() {
-                return name.trim().to_string();
-            }
+/// Progress payload emitted on `scan-progress` while a scan is running.
+#[derive(Debug, Clone, Serialize)]
+pub struct ScanProgressPayload {
+    pub scan_id: Option<String>,
+    pub processed: usize,
+    pub total: usize,
+}
+
+/// Cancellation tokens for in-flight scans, keyed by the caller's `scan_id`.
+#[derive(Default)]
+pub struct ScanManager {
+    active: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+/// Syntax of a skill's frontmatter block, recorded so writes can keep it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FrontmatterFormat {
+    /// `---` fenced YAML.
+    Yaml,
+    /// `+++` fenced TOML (Hugo/Zola style).
+    Toml,
+    /// A leading JSON object.
+    Json,
+}
+
+/// Frontmatter block that was found but could not be parsed.
+#[derive(Debug, Clone)]
+struct FrontmatterError {
+    format: FrontmatterFormat,
+    message: String,
+    /// 1-based line/column in the original file content.
+    line: Option<usize>,
+    column: Option<usize>,
+}
+
+/// Split a fenced block (`---` or `+++`) off the front of `trimmed`.
+/// Returns `None` when the closing fence is missing.
+fn split_fenced<'a>(trimmed: &'a str, fence: &str) -> Option<(&'a str, &'a str)> {
+    let after_first = &trimmed[fence.len()..];
+    let end_idx = after_first.find(&format!("\n{}", fence))?;
+    let block = &after_first[..end_idx];
+    let body = after_first[end_idx + 1 + fence.len()..].trim_start_matches('\n');
+    Some((block, body))
+}
+
+/// 1-based line/column of byte `offset` within `text`.
+fn line_column(text: &str, offset: usize) -> (usize, usize) {
+    let before = &text[..offset.min(text.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.len() - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+    (line, column)
+}
+
+/// Convert TOML to JSON by hand so datetimes become plain strings instead of
+/// the toml crate's private serde representation.
+fn toml_to_json(value: toml::Value) -> serde_json::Value {
+    match value {
+        toml::Value::String(s) => serde_json::Value::String(s),
+        toml::Value::Integer(i) => serde_json::Value::from(i),
+        toml::Value::Float(f) => serde_json::Value::from(f),
+        toml::Value::Boolean(b) => serde_json::Value::Bool(b),
+        toml::Value::Datetime(dt) => serde_json::Value::String(dt.to_string()),
+        toml::Value::Array(items) => {
+            serde_json::Value::Array(items.into_iter().map(toml_to_json).collect())
         }
+        toml::Value::Table(table) => serde_json::Value::Object(
+            table
+                .into_iter()
+                .map(|(key, value)| (key, toml_to_json(value)))
+                .collect(),
+        ),
     }
-    file_stem.to_string()
 }
 
-#[tauri::command]
-pub async fn scan_folder(folder_path: String) -> Result<ScanResult, String> {
-    let path = Path::new(&folder_path);
-    if !path.is_dir() {
-        return Err(format!("Not a directory: {}", folder_path));
+fn parse_yaml_block(
+    block: &str,
+    line_offset: usize,
+) -> Result<serde_json::Value, FrontmatterError> {
+    match serde_yaml::from_str::<serde_yaml::Value>(block) {
+        Ok(yaml_val) => Ok(serde_json::to_value(&yaml_val).unwrap_or(serde_json::Value::Null)),
+        Err(error) => {
+            let location = error.location();
+            Err(FrontmatterError {
+                format: FrontmatterFormat::Yaml,
+                message: error.to_string(),
+                line: location.as_ref().map(|l| l.line() + line_offset),
+                column: location.as_ref().map(|l| l.column()),
+            })
+        }
     }
+}
 
-    let mut skills = Vec::new();
-    let mut skipped = 0usize;
-    let mut errors = 0usize;
-
-    for entry in WalkDir::new(path).follow_links(true).into_iter() {
-        let entry = match entry {
-            Ok(e) => e,
-            Err(_) => {
-                errors += 1;
-                continue;
-            }
-        };
-
-        if !entry.file_type().is_file() {
-            continue;
+fn parse_toml_block(
+    block: &str,
+    line_offset: usize,
+) -> Result<serde_json::Value, FrontmatterError> {
+    match toml::from_str::<toml::Table>(block) {
+        Ok(table) => Ok(toml_to_json(toml::Value::Table(table))),
+        Err(error) => {
+            let position = error.span().map(|span| line_column(block, span.start));
+            Err(FrontmatterError {
+                format: FrontmatterFormat::Toml,
+                message: error.message().to_string(),
+                line: position.map(|(line, _)| line + line_offset),
+                column: position.map(|(_, column)| column),
+            })
         }
+    }
+}
 
-        let file_path = entry.path();
-        let ext = file_path.extension().and_then(|e| e.to_str()).unwrap_or("");
-        if ext != "md" {
-            skipped += 1;
-            continue;
+/// Parse a leading JSON object; the body starts right after its closing brace.
+fn parse_json_prefix(
+    trimmed: &str,
+    line_offset: usize,
+) -> Result<Option<(serde_json::Value, &str)>, FrontmatterError> {
+    let mut stream = serde_json::Deserializer::from_str(trimmed).into_iter::<serde_json::Value>();
+    match stream.next() {
+        Some(Ok(value)) if value.is_object() => {
+            let body = trimmed[stream.byte_offset()..].trim_start_matches(['\r', '\n']);
+            Ok(Some((value, body)))
         }
+        Some(Ok(_)) | None => Ok(None),
+        Some(Err(error)) => Err(FrontmatterError {
+            format: FrontmatterFormat::Json,
+            message: error.to_string(),
+            line: Some(error.line() + line_offset),
+            column: Some(error.column()),
+        }),
+    }
+}
 
-        let content = match std::fs::read_to_string(file_path) {
-            Ok(c) => c,
-            Err(_) => {
-                errors += 1;
-                continue;
-            }
-        };
+pub(crate) type ExtractedFrontmatter<'a> =
+    (Option<(serde_json::Value, FrontmatterFormat)>, &'a str);
 
-        let (frontmatter, body) = extract_frontmatter(&content);
+/// Detect and parse `---` YAML, `+++` TOML or leading-`{` JSON frontmatter,
+/// normalizing all three into a `serde_json::Value`.
+fn try_extract_frontmatter(content: &str) -> Result<ExtractedFrontmatter<'_>, FrontmatterError> {
+    let trimmed = content.trim_start();
 
-        // Skip files with no frontmatter (resilience rule)
-        if frontmatter.is_none() {
-            skipped += 1;
-            continue;
+    // Parsers report positions relative to the block, which starts on the
+    // same line as the opening fence; shift by any leading blank lines.
+    let line_offset = content[..content.len() - trimmed.len()]
+        .matches('\n')
+        .count();
+
+    if trimmed.starts_with("---") {
+        if let Some((block, body)) = split_fenced(trimmed, "---") {
+            let value = parse_yaml_block(block, line_offset)?;
+            return Ok((Some((value, FrontmatterFormat::Yaml)), body));
+        }
+    } else if trimmed.starts_with("+++") {
+        if let Some((block, body)) = split_fenced(trimmed, "+++") {
+            let value = parse_toml_block(block, line_offset)?;
+            return Ok((Some((value, FrontmatterFormat::Toml)), body));
         }
+    } else if trimmed.starts_with('{') {
+        if let Some((value, body)) = parse_json_prefix(trimmed, line_offset)? {
+            return Ok((Some((value, FrontmatterFormat::Json)), body));
+        }
+    }
 
-        let file_stem = file_path
-            .file_stem()
-            .and_then(|s| s.to_str())
-            .unwrap_or("unknown");
+    Ok((None, content))
+}
 
-        let name = derive_name(&frontmatter, file_stem);
+pub(crate) fn extract_frontmatter(content: &str) -> ExtractedFrontmatter<'_> {
+    // Malformed frontmatter is treated as "no frontmatter"; scans surface the
+    // parse error through `ScanDiagnostic` instead.
+    try_extract_frontmatter(content).unwrap_or((None, content))
+}
 
-        skills.push(SkillEntry {
-            path: file_path.to_string_lossy().to_string(),
-            name,
-            frontmatter,
-            body: body.to_string(),
-        });
+pub(crate) fn derive_name(frontmatter: &Option<serde_json::Value>, file_stem: &str) -> String {
+    if let Some(fm) = frontmatter {
+        if let Some(name) = fm.get("name").and_then(|v| v.as_str()) {
+            if !name.trim().is_empty() {
+                return name.trim().to_string();
+            }
+        }
     }
-
-    Ok(ScanResult {
-        skills,
-        skipped,
-        errors,
-    })
+    file_stem.to_string()
 }
 
 /// Stage 1: Lightweight index pass — frontmatter only, no body parsing.
@@ -128,6 +325,10 @@ pub struct SkillIndex {
     pub path: String,
     pub name: String,
     pub frontmatter: Option<serde_json::Value>,
+    #[serde(default)]
+    pub frontmatter_format: Option<FrontmatterFormat>,
+    #[serde(default)]
+    pub bundle: Option<SkillBundle>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -135,135 +336,871 @@ pub struct IndexResult {
     pub skills: Vec<SkillIndex>,
     pub skipped: usize,
     pub errors: usize,
+    pub cancelled: bool,
+    pub cache: CacheReport,
+    pub diagnostics: Vec<ScanDiagnostic>,
+    #[serde(default)]
+    pub id_collisions: Vec<IdCollision>,
 }
 
-#[tauri::command]
-pub async fn scan_folder_index(folder_path: String) -> Result<IndexResult, String> {
-    let path = Path::new(&folder_path);
-    if !path.is_dir() {
-        return Err(format!("Not a directory: {}", folder_path));
+/// Summary of an index scan, sent as the final message of a streamed scan.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexSummary {
+    pub skipped: usize,
+    pub errors: usize,
+    pub cancelled: bool,
+    pub cache: CacheReport,
+    pub diagnostics: Vec<ScanDiagnostic>,
+    #[serde(default)]
+    pub id_collisions: Vec<IdCollision>,
+}
+
+/// Messages sent over the channel passed to `scan_folder_index_stream`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", content = "data", rename_all = "snake_case")]
+pub enum IndexStreamEvent {
+    Batch { skills: Vec<SkillIndex> },
+    Finished(IndexSummary),
+}
+
+/// Outcome of parsing a single candidate file during a scan.
+enum FileOutcome<T> {
+    Parsed(T),
+    Rejected(ScanDiagnostic),
+}
+
+/// Counters for a finished (or cancelled) scan; parsed items are handed to
+/// the caller's sink batch by batch.
+struct ScanOutcome {
+    skipped: usize,
+    errors: usize,
+    cancelled: bool,
+    diagnostics: Vec<ScanDiagnostic>,
+}
+
+/// A markdown file to parse, with its bundle when it is a `SKILL.md`.
+struct ScanCandidate {
+    path: PathBuf,
+    bundle: Option<SkillBundle>,
+}
+
+/// Markdown candidates found by the walk plus problems met while walking.
+struct WalkOutcome {
+    candidates: Vec<ScanCandidate>,
+    skipped: usize,
+    diagnostics: Vec<ScanDiagnostic>,
+}
+
+/// Index-scan result for one file, together with its refreshed cache entry.
+struct IndexedFile {
+    key: String,
+    entry: CachedEntry,
+    status: CacheStatus,
+}
+
+/// Build the directory walker for a scan: honours `.gitignore`, `.ignore`
+/// and `.skillsignore` (even outside a git repo), skips the autogit
+/// directory blacklist and the workspace template folder, and applies the
+/// caller's include/exclude globs. Symlinks are followed only while they
+/// resolve inside `root`; one that points outside is skipped with everything
+/// below it.
+fn build_walker(root: &Path, options: &ScanOptions) -> Result<ignore::Walk, String> {
+    let mut overrides = OverrideBuilder::new(root);
+    for glob in &options.include {
+        overrides
+            .add(glob)
+            .map_err(|e| format!("Invalid include glob {:?}: {}", glob, e))?;
     }
+    for glob in &options.exclude {
+        overrides
+            .add(&format!("!{}", glob))
+            .map_err(|e| format!("Invalid exclude glob {:?}: {}", glob, e))?;
+    }
+    let overrides = overrides
+        .build()
+        .map_err(|e| format!("Invalid scan globs: {}", e))?;
+    let canonical_root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
 
-    let mut skills = Vec::new();
-    let mut skipped = 0usize;
-    let mut errors = 0usize;
-
-    for entry in WalkDir::new(path).follow_links(true).into_iter() {
-        let entry = match entry {
-            Ok(e) => e,
-            Err(_) => {
-                errors += 1;
-                continue;
+    let walker = WalkBuilder::new(root)
+        .follow_links(true)
+        .hidden(false)
+        .require_git(false)
+        .add_custom_ignore_filename(SKILLS_IGNORE_FILE)
+        .overrides(overrides)
+        .filter_entry(move |entry| {
+            if entry.depth() > 0 && entry.path_is_symlink() {
+                let inside = entry
+                    .path()
+                    .canonicalize()
+                    .is_ok_and(|target| target.starts_with(&canonical_root));
+                if !inside {
+                    return false;
+                }
             }
-        };
+            let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+            !(is_dir
+                && entry.file_name().to_str().is_some_and(|name| {
+                    EXCLUDED_DIR_NAMES.contains(&name) || name == WORKSPACE_TEMPLATE_DIR
+                }))
+        })
+        .build();
+
+    Ok(walker)
+}
+
+/// Map a walker error to a diagnostic, unwrapping the path/depth context
+/// the `ignore` crate layers around the underlying cause.
+fn walk_error_diagnostic(root: &Path, error: &ignore::Error) -> ScanDiagnostic {
+    let mut path: Option<&Path> = None;
+    let mut cause = error;
+    loop {
+        match cause {
+            ignore::Error::WithPath { path: p, err } => {
+                path = path.or(Some(p.as_path()));
+                cause = err;
+            }
+            ignore::Error::WithDepth { err, .. } | ignore::Error::WithLineNumber { err, .. } => {
+                cause = err;
+            }
+            _ => break,
+        }
+    }
+
+    let (code, path) = match cause {
+        ignore::Error::Loop { child, .. } => (DiagnosticCode::SymlinkLoop, child.as_path()),
+        _ => (DiagnosticCode::IoError, path.unwrap_or(root)),
+    };
+    ScanDiagnostic::new(path, code, Some(error.to_string()))
+}
+
+/// Walk `root` and collect every file the ignore rules let through.
+/// Paths are sorted so results come back in a stable order regardless of
+/// thread scheduling.
+fn walk_files(
+    root: &Path,
+    options: &ScanOptions,
+    cancel: &AtomicBool,
+) -> Result<(Vec<PathBuf>, Vec<ScanDiagnostic>), String> {
+    let mut files = Vec::new();
+    let mut diagnostics = Vec::new();
+
+    for entry in build_walker(root, options)? {
+        if cancel.load(Ordering::Relaxed) {
+            break;
+        }
 
-        if !entry.file_type().is_file() {
+        match entry {
+            Ok(entry) if entry.file_type().is_some_and(|t| t.is_file()) => {
+                files.push(entry.into_path());
+            }
+            Ok(_) => {}
+            Err(error) => diagnostics.push(walk_error_diagnostic(root, &error)),
+        }
+    }
+
+    files.sort();
+    Ok((files, diagnostics))
+}
+
+/// Collect the markdown files to parse. Files inside a directory bundle are
+/// resources of its `SKILL.md`, not skills of their own.
+fn collect_markdown_paths(
+    root: &Path,
+    options: &ScanOptions,
+    cancel: &AtomicBool,
+) -> Result<WalkOutcome, String> {
+    let (files, diagnostics) = walk_files(root, options, cancel)?;
+    let layout = BundleLayout::from_files(&files);
+
+    let mut candidates = Vec::new();
+    let mut skipped = 0usize;
+    for path in files {
+        if layout.owns(&path) {
             continue;
         }
 
-        let file_path = entry.path();
-        let ext = file_path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
         if ext != "md" {
             skipped += 1;
             continue;
         }
 
-        // Read only enough to extract frontmatter (first ~4KB is usually sufficient)
-        let content = match std::fs::read_to_string(file_path) {
-            Ok(c) => c,
-            Err(_) => {
-                errors += 1;
-                continue;
-            }
-        };
+        let bundle = layout.bundle_for(&path);
+        candidates.push(ScanCandidate { path, bundle });
+    }
 
-        let (frontmatter, _body) = extract_frontmatter(&content);
+    Ok(WalkOutcome {
+        candidates,
+        skipped,
+        diagnostics,
+    })
+}
 
-        if frontmatter.is_none() {
-            skipped += 1;
-            continue;
+/// Every file in the workspace the scan would walk, in path order.
+pub(crate) fn workspace_files(root: &Path) -> Result<Vec<PathBuf>, String> {
+    let (files, _) = walk_files(root, &ScanOptions::default(), &AtomicBool::new(false))?;
+    Ok(files)
+}
+
+/// Every markdown file in the workspace the scan would walk, bundle
+/// resources included (they can link to skills too). Used by commands that
+/// rewrite references across the workspace.
+pub(crate) fn workspace_markdown_files(root: &Path) -> Result<Vec<PathBuf>, String> {
+    Ok(workspace_files(root)?
+        .into_iter()
+        .filter(|path| path.extension().and_then(|e| e.to_str()) == Some("md"))
+        .collect())
+}
+
+/// The markdown files a scan would parse as skills, bundle resources
+/// excluded.
+pub(crate) fn workspace_skill_files(root: &Path) -> Result<Vec<PathBuf>, String> {
+    let outcome = collect_markdown_paths(root, &ScanOptions::default(), &AtomicBool::new(false))?;
+    Ok(outcome
+        .candidates
+        .into_iter()
+        .map(|candidate| candidate.path)
+        .collect())
+}
+
+/// Name a skill file falls back to when its frontmatter has none: the bundle
+/// directory for a `SKILL.md`, otherwise the file stem.
+pub(crate) fn fallback_skill_name(path: &Path) -> &str {
+    if is_bundle_manifest(path) {
+        if let Some(dir) = bundle_dir_name(path) {
+            return dir;
+        }
+    }
+    file_stem_of(path)
+}
+
+/// Resolve the bundle for a single `SKILL.md` opened outside a scan.
+fn bundle_for_manifest(manifest: &Path) -> Option<SkillBundle> {
+    if !is_bundle_manifest(manifest) {
+        return None;
+    }
+    let root = manifest.parent()?;
+    let (files, _) = walk_files(root, &ScanOptions::default(), &AtomicBool::new(false)).ok()?;
+    BundleLayout::from_files(&files).bundle_for(manifest)
+}
+
+/// Read a candidate file, refusing anything over `MAX_SKILL_FILE_BYTES`.
+fn read_skill_bytes(file_path: &Path, len: u64) -> Result<Vec<u8>, ScanDiagnostic> {
+    if len > MAX_SKILL_FILE_BYTES {
+        return Err(ScanDiagnostic::new(
+            file_path,
+            DiagnosticCode::TooLarge,
+            Some(format!(
+                "{} bytes exceeds the {} byte limit",
+                len, MAX_SKILL_FILE_BYTES
+            )),
+        ));
+    }
+
+    std::fs::read(file_path).map_err(|error| {
+        ScanDiagnostic::new(file_path, DiagnosticCode::IoError, Some(error.to_string()))
+    })
+}
+
+fn decode_skill_bytes(file_path: &Path, bytes: Vec<u8>) -> Result<String, ScanDiagnostic> {
+    String::from_utf8(bytes).map_err(|error| {
+        ScanDiagnostic::new(file_path, DiagnosticCode::NonUtf8, Some(error.to_string()))
+    })
+}
+
+/// Extract frontmatter, turning "no frontmatter" and YAML errors into
+/// diagnostics (resilience rule: such files are skipped, not fatal).
+fn parse_skill_content<'a>(
+    file_path: &Path,
+    content: &'a str,
+) -> Result<(serde_json::Value, FrontmatterFormat, &'a str), ScanDiagnostic> {
+    match try_extract_frontmatter(content) {
+        Ok((Some((frontmatter, format)), body)) => Ok((frontmatter, format, body)),
+        Ok((None, _)) => Err(ScanDiagnostic::new(
+            file_path,
+            DiagnosticCode::NoFrontmatter,
+            None,
+        )),
+        Err(error) => {
+            let code = match error.format {
+                FrontmatterFormat::Yaml => DiagnosticCode::MalformedYaml,
+                FrontmatterFormat::Toml => DiagnosticCode::MalformedToml,
+                FrontmatterFormat::Json => DiagnosticCode::MalformedJson,
+            };
+            Err(ScanDiagnostic {
+                line: error.line,
+                column: error.column,
+                ..ScanDiagnostic::new(file_path, code, Some(error.message))
+            })
         }
+    }
+}
+
+fn file_stem_of(file_path: &Path) -> &str {
+    file_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("unknown")
+}
+
+fn parse_skill_file(candidate: &ScanCandidate) -> FileOutcome<SkillEntry> {
+    let file_path = candidate.path.as_path();
+    let parsed = std::fs::metadata(file_path)
+        .map_err(|error| {
+            ScanDiagnostic::new(file_path, DiagnosticCode::IoError, Some(error.to_string()))
+        })
+        .and_then(|metadata| {
+            let bytes = read_skill_bytes(file_path, metadata.len())?;
+            let hash = scan_cache::content_hash(&bytes);
+            let content = decode_skill_bytes(file_path, bytes)?;
+            let (frontmatter, format, body) = parse_skill_content(file_path, &content)?;
+            let mtime = scan_cache::mtime_millis(&metadata);
+            Ok((frontmatter, format, body.to_string(), hash, mtime))
+        });
+
+    let (frontmatter, format, body, hash, mtime) = match parsed {
+        Ok(parsed) => parsed,
+        Err(diagnostic) => return FileOutcome::Rejected(diagnostic),
+    };
 
-        let file_stem = file_path
-            .file_stem()
-            .and_then(|s| s.to_str())
-            .unwrap_or("unknown");
+    let frontmatter = Some(frontmatter);
+    let name = derive_name(&frontmatter, fallback_skill_name(&candidate.path));
+
+    FileOutcome::Parsed(SkillEntry {
+        path: file_path.to_string_lossy().to_string(),
+        name,
+        frontmatter,
+        frontmatter_format: Some(format),
+        body,
+        bundle: candidate.bundle.clone(),
+        content_hash: Some(hash),
+        mtime: Some(mtime),
+        document: None,
+    })
+}
+
+/// Index a single file, serving it from `cache` when mtime/size or the
+/// content hash show it is unchanged. Skipped files are cached too.
+fn index_file_cached(candidate: &ScanCandidate, cache: &ScanCache) -> FileOutcome<IndexedFile> {
+    let file_path = candidate.path.as_path();
+    let metadata = match std::fs::metadata(file_path) {
+        Ok(m) => m,
+        Err(error) => {
+            return FileOutcome::Rejected(ScanDiagnostic::new(
+                file_path,
+                DiagnosticCode::IoError,
+                Some(error.to_string()),
+            ))
+        }
+    };
+    let key = cache_key(file_path);
+
+    // Read lazily: a matching mtime/size never touches the file content.
+    let mut bytes: Option<Vec<u8>> = None;
+    if let Some(cached) = cache.get(&key) {
+        let hit = scan_cache::revalidate(cached, &metadata, || {
+            bytes = read_skill_bytes(file_path, metadata.len()).ok();
+            bytes.clone()
+        });
+        if let Some(mut entry) = hit {
+            // Resources can change without the manifest changing, so the
+            // bundle always comes from the current walk.
+            if let Some(skill) = entry.skill.as_mut() {
+                skill.bundle = candidate.bundle.clone();
+            }
+            return FileOutcome::Parsed(IndexedFile {
+                key,
+                entry,
+                status: CacheStatus::Hit,
+            });
+        }
+    }
 
-        let name = derive_name(&frontmatter, file_stem);
+    let bytes = match bytes.map_or_else(|| read_skill_bytes(file_path, metadata.len()), Ok) {
+        Ok(b) => b,
+        Err(diagnostic) => return FileOutcome::Rejected(diagnostic),
+    };
+    let hash = scan_cache::content_hash(&bytes);
 
-        skills.push(SkillIndex {
+    let parsed = decode_skill_bytes(file_path, bytes).and_then(|content| {
+        let (frontmatter, format, _body) = parse_skill_content(file_path, &content)?;
+        let frontmatter = Some(frontmatter);
+        Ok(SkillIndex {
             path: file_path.to_string_lossy().to_string(),
-            name,
+            name: derive_name(&frontmatter, fallback_skill_name(&candidate.path)),
             frontmatter,
-        });
+            frontmatter_format: Some(format),
+            bundle: candidate.bundle.clone(),
+        })
+    });
+    let (skill, diagnostic) = match parsed {
+        Ok(skill) => (Some(skill), None),
+        Err(diagnostic) => (None, Some(diagnostic)),
+    };
+
+    FileOutcome::Parsed(IndexedFile {
+        key,
+        entry: CachedEntry {
+            mtime: scan_cache::mtime_millis(&metadata),
+            size: metadata.len(),
+            hash,
+            skill,
+            diagnostic,
+        },
+        status: CacheStatus::Updated,
+    })
+}
+
+/// Scan `root` across the rayon thread pool, running `parse` on every
+/// markdown file.
+///
+/// Files are parsed in path-ordered batches of `SCAN_BATCH_SIZE`; each batch
+/// is handed to `sink` as soon as it completes, so concatenating the batches
+/// gives a stable order. `cancel` is polled between files; once set,
+/// remaining files are dropped and the outcome is flagged as cancelled.
+/// `progress` receives `(processed, total)` from any worker thread.
+fn scan_files<T: Send>(
+    root: &Path,
+    options: &ScanOptions,
+    cancel: &AtomicBool,
+    progress: &(dyn Fn(usize, usize) + Sync),
+    parse: &(dyn Fn(&ScanCandidate) -> FileOutcome<T> + Sync),
+    sink: &mut dyn FnMut(Vec<T>),
+) -> Result<ScanOutcome, String> {
+    let WalkOutcome {
+        candidates,
+        mut skipped,
+        mut diagnostics,
+    } = collect_markdown_paths(root, options, cancel)?;
+    let mut errors = diagnostics.len();
+    let total = candidates.len();
+    let processed = AtomicUsize::new(0);
+
+    for chunk in candidates.chunks(SCAN_BATCH_SIZE) {
+        if cancel.load(Ordering::Relaxed) {
+            break;
+        }
+
+        let outcomes: Vec<Option<FileOutcome<T>>> = chunk
+            .par_iter()
+            .map(|candidate| {
+                if cancel.load(Ordering::Relaxed) {
+                    return None;
+                }
+                let outcome = parse(candidate);
+                let done = processed.fetch_add(1, Ordering::Relaxed) + 1;
+                if done.is_multiple_of(SCAN_PROGRESS_INTERVAL) {
+                    progress(done, total);
+                }
+                Some(outcome)
+            })
+            .collect();
+
+        let mut items = Vec::new();
+        for outcome in outcomes.into_iter().flatten() {
+            match outcome {
+                FileOutcome::Parsed(item) => items.push(item),
+                FileOutcome::Rejected(diagnostic) => {
+                    if diagnostic.is_error() {
+                        errors += 1;
+                    } else {
+                        skipped += 1;
+                    }
+                    diagnostics.push(diagnostic);
+                }
+            }
+        }
+        if !items.is_empty() {
+            sink(items);
+        }
     }
 
-    Ok(IndexResult {
-        skills,
+    let cancelled = cancel.load(Ordering::Relaxed);
+    progress(processed.load(Ordering::Relaxed), total);
+    diagnostics.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(ScanOutcome {
         skipped,
         errors,
+        cancelled,
+        diagnostics,
+    })
+}
+
+/// Register a cancellation token for `scan_id` (if given) and run the scan,
+/// emitting `scan-progress` events to the calling window.
+fn run_scan<T: Send>(
+    window: &Window,
+    state: &ScanManager,
+    folder_path: &str,
+    scan_id: Option<String>,
+    options: &ScanOptions,
+    parse: &(dyn Fn(&ScanCandidate) -> FileOutcome<T> + Sync),
+    sink: &mut dyn FnMut(Vec<T>),
+) -> Result<ScanOutcome, String> {
+    let path = Path::new(folder_path);
+    if !path.is_dir() {
+        return Err(format!("Not a directory: {}", folder_path));
+    }
+
+    let cancel = Arc::new(AtomicBool::new(false));
+    if let Some(id) = &scan_id {
+        let mut guard = state
+            .active
+            .lock()
+            .map_err(|_| "scan state lock poisoned".to_string())?;
+        guard.insert(id.clone(), Arc::clone(&cancel));
+    }
+
+    let progress = |processed: usize, total: usize| {
+        let _ = window.emit(
+            SCAN_PROGRESS_EVENT,
+            ScanProgressPayload {
+                scan_id: scan_id.clone(),
+                processed,
+                total,
+            },
+        );
+    };
+    let outcome = scan_files(path, options, &cancel, &progress, parse, sink);
+
+    if let Some(id) = &scan_id {
+        if let Ok(mut guard) = state.active.lock() {
+            guard.remove(id);
+        }
+    }
+
+    outcome
+}
+
+/// Run a cached index scan, passing each batch of skills to `on_batch` and
+/// returning the final summary. Shared by the one-shot and streamed modes.
+fn run_index_scan(
+    window: &Window,
+    state: &ScanManager,
+    folder_path: &str,
+    scan_id: Option<String>,
+    options: &ScanOptions,
+    on_batch: &mut dyn FnMut(Vec<SkillIndex>),
+) -> Result<IndexSummary, String> {
+    let app_data_dir = window.app_handle().path().app_data_dir().ok();
+    let mut cache = ScanCache::load(Path::new(folder_path), app_data_dir);
+
+    let mut report = CacheReport::default();
+    let mut skipped_cached = 0usize;
+    let mut errors_cached = 0usize;
+    let mut cached_diagnostics = Vec::new();
+    let mut named: Vec<(String, String)> = Vec::new();
+    let mut seen = BTreeMap::new();
+    let outcome = run_scan(
+        window,
+        state,
+        folder_path,
+        scan_id,
+        options,
+        &|candidate| index_file_cached(candidate, &cache),
+        &mut |batch: Vec<IndexedFile>| {
+            let mut skills = Vec::new();
+            for indexed in batch {
+                match indexed.status {
+                    CacheStatus::Hit => report.hits.push(indexed.key.clone()),
+                    CacheStatus::Updated => report.updated.push(indexed.key.clone()),
+                }
+                if let Some(skill) = &indexed.entry.skill {
+                    skills.push(skill.clone());
+                } else if let Some(diagnostic) = &indexed.entry.diagnostic {
+                    if diagnostic.is_error() {
+                        errors_cached += 1;
+                    } else {
+                        skipped_cached += 1;
+                    }
+                    cached_diagnostics.push(diagnostic.clone());
+                }
+                seen.insert(indexed.key, indexed.entry);
+            }
+            named.extend(
+                skills
+                    .iter()
+                    .map(|skill| (skill.path.clone(), skill.name.clone())),
+            );
+            if !skills.is_empty() {
+                on_batch(skills);
+            }
+        },
+    )?;
+
+    // Files a filtered scan skipped are not gone; only a full scan may
+    // prune them from the cache and report them deleted.
+    report.deleted = cache.replace(seen, !outcome.cancelled && options.is_unfiltered());
+    if let Err(error) = cache.save() {
+        eprintln!("[scan_folder_index] cache save failed: {error}");
+    }
+
+    let mut diagnostics = outcome.diagnostics;
+    diagnostics.extend(cached_diagnostics);
+    diagnostics.sort_by(|a, b| a.path.cmp(&b.path));
+    let (_, id_collisions) = assign_node_ids(
+        &named
+            .iter()
+            .map(|(path, name)| (path.as_str(), name.as_str()))
+            .collect::<Vec<_>>(),
+    );
+
+    Ok(IndexSummary {
+        skipped: outcome.skipped + skipped_cached,
+        errors: outcome.errors + errors_cached,
+        cancelled: outcome.cancelled,
+        cache: report,
+        diagnostics,
+        id_collisions,
+    })
+}
+
+/// Cached index scan of a whole workspace, for commands that evaluate the
+/// skills themselves rather than streaming them to the frontend.
+pub(crate) fn index_workspace(
+    window: &Window,
+    state: &ScanManager,
+    root: &Path,
+) -> Result<Vec<SkillIndex>, String> {
+    let mut skills = Vec::new();
+    run_index_scan(
+        window,
+        state,
+        &root.to_string_lossy(),
+        None,
+        &ScanOptions::default(),
+        &mut |batch| skills.extend(batch),
+    )?;
+    Ok(skills)
+}
+
+/// Full scan: frontmatter and body for every skill under `folder_path`.
+///
+/// `scan_id` — optional caller-chosen id; pass it to `cancel_scan` to stop
+/// the scan early and to match `scan-progress` events.
+/// `options` — extra include/exclude globs on top of the ignore files.
+#[tauri::command]
+pub async fn scan_folder(
+    window: Window,
+    state: State<'_, ScanManager>,
+    workspaces: State<'_, WorkspaceRegistry>,
+    folder_path: String,
+    scan_id: Option<String>,
+    options: Option<ScanOptions>,
+) -> Result<ScanResult, String> {
+    workspaces.resolve(&folder_path)?;
+    let mut skills = Vec::new();
+    let outcome = run_scan(
+        &window,
+        &state,
+        &folder_path,
+        scan_id,
+        &options.unwrap_or_default(),
+        &parse_skill_file,
+        &mut |batch| skills.extend(batch),
+    )?;
+
+    let (_, id_collisions) = assign_node_ids(
+        &skills
+            .iter()
+            .map(|skill| (skill.path.as_str(), skill.name.as_str()))
+            .collect::<Vec<_>>(),
+    );
+
+    Ok(ScanResult {
+        skills,
+        skipped: outcome.skipped,
+        errors: outcome.errors,
+        cancelled: outcome.cancelled,
+        diagnostics: outcome.diagnostics,
+        id_collisions,
+    })
+}
+
+/// Index scan backed by the persistent scan cache: only new or changed files
+/// are re-parsed, and `cache` reports which entries were hits, updated or
+/// deleted since the previous scan. Scans with include/exclude globs never
+/// report deletions.
+#[tauri::command]
+pub async fn scan_folder_index(
+    window: Window,
+    state: State<'_, ScanManager>,
+    workspaces: State<'_, WorkspaceRegistry>,
+    folder_path: String,
+    scan_id: Option<String>,
+    options: Option<ScanOptions>,
+) -> Result<IndexResult, String> {
+    workspaces.resolve(&folder_path)?;
+    let mut skills = Vec::new();
+    let summary = run_index_scan(
+        &window,
+        &state,
+        &folder_path,
+        scan_id,
+        &options.unwrap_or_default(),
+        &mut |batch| skills.extend(batch),
+    )?;
+
+    Ok(IndexResult {
+        skills,
+        skipped: summary.skipped,
+        errors: summary.errors,
+        cancelled: summary.cancelled,
+        cache: summary.cache,
+        diagnostics: summary.diagnostics,
+        id_collisions: summary.id_collisions,
     })
 }
 
+/// Streaming variant of `scan_folder_index`.
+///
+/// Sends `SkillIndex` entries over `on_event` in path-ordered batches as they
+/// are parsed, then a single `finished` message with the summary, so the
+/// sidebar and graph can fill in progressively on large vaults.
+#[tauri::command]
+pub async fn scan_folder_index_stream(
+    window: Window,
+    state: State<'_, ScanManager>,
+    workspaces: State<'_, WorkspaceRegistry>,
+    folder_path: String,
+    scan_id: Option<String>,
+    options: Option<ScanOptions>,
+    on_event: Channel<IndexStreamEvent>,
+) -> Result<(), String> {
+    workspaces.resolve(&folder_path)?;
+    let summary = run_index_scan(
+        &window,
+        &state,
+        &folder_path,
+        scan_id,
+        &options.unwrap_or_default(),
+        &mut |skills| {
+            let _ = on_event.send(IndexStreamEvent::Batch { skills });
+        },
+    )?;
+
+    on_event
+        .send(IndexStreamEvent::Finished(summary))
+        .map_err(|e| format!("Failed to send scan summary: {e}"))
+}
+
+/// Signal a running scan to stop. Returns false when no scan with that id
+/// is in flight (already finished or never started).
+#[tauri::command]
+pub async fn cancel_scan(state: State<'_, ScanManager>, scan_id: String) -> Result<bool, String> {
+    let guard = state
+        .active
+        .lock()
+        .map_err(|_| "scan state lock poisoned".to_string())?;
+
+    match guard.get(&scan_id) {
+        Some(cancel) => {
+            cancel.store(true, Ordering::Relaxed);
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
 /// Write updated markdown content back to a skill file on disk.
 /// Security: only overwrites existing .md files — no arbitrary file creation.
+///
+/// The write is atomic (temp file + fsync + rename). When `expected_hash` or
+/// `expected_mtime` is given and the file no longer matches, nothing is
+/// written and a `conflict` error is returned.
 #[tauri::command]
-pub async fn write_skill_file(file_path: String, content: String) -> Result<String, String> {
-    let path = Path::new(&file_path);
+pub async fn write_skill_file(
+    workspaces: State<'_, WorkspaceRegistry>,
+    file_path: String,
+    content: String,
+    expected_hash: Option<String>,
+    expected_mtime: Option<u64>,
+) -> Result<WrittenFile, WriteError> {
+    // Must be inside an open workspace. Checked before anything is stat'ed,
+    // and a missing path outside it gets the same error, so the result never
+    // tells whether a file exists outside the sandbox.
+    let path = workspaces
+        .resolve(&file_path)
+        .map_err(|_| WriteError::OutsideWorkspace {
+            path: file_path.clone(),
+        })?;
 
     // Must be an existing file
     if !path.is_file() {
-        return Err(format!("Not a file: {}", file_path));
+        return Err(WriteError::NotFound { path: file_path });
     }
+    let path = path.as_path();
 
     // Must be a .md file
     let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
     if ext != "md" {
-        return Err(format!(
-            "Refusing to write non-markdown file: {}",
-            file_path
-        ));
+        return Err(WriteError::NotMarkdown { path: file_path });
     }
 
-    std::fs::write(path, &content).map_err(|e| format!("Write failed: {}", e))?;
-
-    Ok(file_path)
+    let expected = WriteExpectation {
+        hash: expected_hash,
+        mtime: expected_mtime,
+    };
+    check_expectation(path, &expected)?;
+    write_atomic(path, content.as_bytes())
 }
 
 /// Stage 2: On-demand body parse for a single file (called when user selects a skill).
+///
+/// `include_document` — also return the parsed body structure (headings,
+/// wiki-links, code blocks, tasks and file links with byte spans).
 #[tauri::command]
-pub async fn read_skill_file(file_path: String) -> Result<SkillEntry, String> {
-    let path = Path::new(&file_path);
+pub async fn read_skill_file(
+    workspaces: State<'_, WorkspaceRegistry>,
+    file_path: String,
+    include_document: Option<bool>,
+) -> Result<SkillEntry, String> {
+    let path = workspaces.resolve(&file_path)?;
+    let path = path.as_path();
     if !path.is_file() {
         return Err(format!("Not a file: {}", file_path));
     }
 
+    let metadata = std::fs::metadata(path).map_err(|e| e.to_string())?;
     let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
-    let (frontmatter, body) = extract_frontmatter(&content);
-    let file_stem = path
-        .file_stem()
-        .and_then(|s| s.to_str())
-        .unwrap_or("unknown");
-    let name = derive_name(&frontmatter, file_stem);
+    let (parsed, body) = extract_frontmatter(&content);
+    let (frontmatter, frontmatter_format) = parsed.unzip();
+    let candidate = ScanCandidate {
+        path: path.to_path_buf(),
+        bundle: bundle_for_manifest(path),
+    };
+    let name = derive_name(&frontmatter, fallback_skill_name(&candidate.path));
+    let document = include_document.unwrap_or(false).then(|| SkillDocument {
+        // The body is always a suffix of the file content.
+        body_offset: content.len() - body.len(),
+        ..parse_document(body)
+    });
 
     Ok(SkillEntry {
         path: file_path,
         name,
         frontmatter,
+        frontmatter_format,
         body: body.to_string(),
+        bundle: candidate.bundle,
+        content_hash: Some(scan_cache::content_hash(content.as_bytes())),
+        mtime: Some(scan_cache::mtime_millis(&metadata)),
+        document,
     })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support::TempDir;
 
     #[test]
     fn test_extract_frontmatter_valid() {
         let content = "---\nname: test-skill\ntype: skill\n---\n# Body\nHello";
         let (fm, body) = extract_frontmatter(content);
         assert!(fm.is_some());
-        assert_eq!(fm.unwrap()["name"], "test-skill");
+        assert_eq!(fm.unwrap().0["name"], "test-skill");
         assert!(body.starts_with("# Body"));
     }
 
@@ -275,6 +1212,50 @@ mod tests {
         assert_eq!(body, content);
     }
 
+    #[test]
+    fn test_try_extract_frontmatter_reports_yaml_position() {
+        let content = "\n---\nname: ok\ntags: [a, b\n---\nbody";
+        let error = try_extract_frontmatter(content).unwrap_err();
+        assert!(!error.message.is_empty());
+        assert!(error.line.unwrap() >= 4);
+        assert!(error.column.is_some());
+
+        // The lenient wrapper still treats malformed YAML as no frontmatter.
+        let (fm, body) = extract_frontmatter(content);
+        assert!(fm.is_none());
+        assert_eq!(body, content);
+    }
+
+    #[test]
+    fn test_extract_frontmatter_toml() {
+        let content = "+++\nname = \"zola-skill\"\ndate = 2024-01-02\ntags = [\"a\"]\n+++\n# Body";
+        let (parsed, body) = try_extract_frontmatter(content).unwrap();
+        let (fm, format) = parsed.unwrap();
+        assert_eq!(format, FrontmatterFormat::Toml);
+        assert_eq!(fm["name"], "zola-skill");
+        assert_eq!(fm["date"], "2024-01-02");
+        assert_eq!(fm["tags"][0], "a");
+        assert_eq!(body, "# Body");
+    }
+
+    #[test]
+    fn test_extract_frontmatter_json() {
+        let content = "{\n  \"name\": \"json-skill\",\n  \"type\": \"hook\"\n}\n# Body";
+        let (parsed, body) = try_extract_frontmatter(content).unwrap();
+        let (fm, format) = parsed.unwrap();
+        assert_eq!(format, FrontmatterFormat::Json);
+        assert_eq!(fm["type"], "hook");
+        assert_eq!(body, "# Body");
+    }
+
+    #[test]
+    fn test_try_extract_frontmatter_reports_toml_position() {
+        let content = "+++\nname = \"ok\"\ntags = [\n+++\nbody";
+        let error = try_extract_frontmatter(content).unwrap_err();
+        assert_eq!(error.format, FrontmatterFormat::Toml);
+        assert!(error.line.is_some());
+    }
+
     #[test]
     fn test_derive_name_from_frontmatter() {
         let fm = serde_json::json!({"name": "my-skill"});
@@ -285,4 +1266,190 @@ mod tests {
     fn test_derive_name_fallback() {
         assert_eq!(derive_name(&None, "file-stem"), "file-stem");
     }
+
+    fn temp_vault(label: &str) -> TempDir {
+        let dir = TempDir::new(&format!("scan-{}", label));
+        std::fs::create_dir_all(dir.join("nested")).unwrap();
+        for (file, name) in [("b.md", "b"), ("a.md", "a"), ("nested/c.md", "c")] {
+            std::fs::write(dir.join(file), format!("---\nname: {name}\n---\nbody")).unwrap();
+        }
+        std::fs::write(dir.join("plain.md"), "# no frontmatter").unwrap();
+        std::fs::write(dir.join("notes.txt"), "ignored").unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_scan_skills_stable_order() {
+        let dir = temp_vault("order");
+        let cancel = AtomicBool::new(false);
+        let mut skills = Vec::new();
+        let outcome = scan_files(
+            dir.path(),
+            &ScanOptions::default(),
+            &cancel,
+            &|_, _| {},
+            &parse_skill_file,
+            &mut |batch| skills.extend(batch),
+        )
+        .unwrap();
+        let names: Vec<_> = skills.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["a", "b", "c"]);
+        assert_eq!(outcome.skipped, 2);
+        assert_eq!(outcome.diagnostics.len(), 1);
+        assert_eq!(outcome.diagnostics[0].code, DiagnosticCode::NoFrontmatter);
+        assert!(!outcome.cancelled);
+    }
+
+    fn scanned_names(dir: &Path, options: &ScanOptions) -> Vec<String> {
+        let cancel = AtomicBool::new(false);
+        let mut skills = Vec::new();
+        scan_files(
+            dir,
+            options,
+            &cancel,
+            &|_, _| {},
+            &parse_skill_file,
+            &mut |batch| skills.extend(batch),
+        )
+        .unwrap();
+        skills.into_iter().map(|s| s.name).collect()
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_scan_skips_symlinks_leaving_the_root() {
+        let dir = temp_vault("symlinks");
+        let outside = TempDir::new("scan-symlinks-outside");
+        std::fs::write(outside.join("secret.md"), "---\nname: secret\n---\n").unwrap();
+        std::os::unix::fs::symlink(outside.path(), dir.join("linked")).unwrap();
+        std::os::unix::fs::symlink(outside.join("secret.md"), dir.join("leak.md")).unwrap();
+        std::os::unix::fs::symlink(dir.join("nested"), dir.join("alias")).unwrap();
+
+        assert_eq!(
+            scanned_names(dir.path(), &ScanOptions::default()),
+            vec!["a", "c", "b", "c"]
+        );
+        let markdown = workspace_markdown_files(dir.path()).unwrap();
+        assert!(markdown.iter().all(|path| !path.ends_with("secret.md")));
+        assert!(markdown.iter().all(|path| !path.ends_with("leak.md")));
+    }
+
+    #[test]
+    fn test_scan_honours_ignore_files_and_blacklist() {
+        let dir = temp_vault("ignore");
+        std::fs::create_dir_all(dir.join("node_modules/pkg")).unwrap();
+        std::fs::write(dir.join("node_modules/pkg/d.md"), "---\nname: d\n---\n").unwrap();
+        std::fs::write(dir.join(".skillsignore"), "nested/\n").unwrap();
+        std::fs::write(dir.join(".gitignore"), "b.md\n").unwrap();
+
+        assert_eq!(
+            scanned_names(dir.path(), &ScanOptions::default()),
+            vec!["a"]
+        );
+    }
+
+    #[test]
+    fn test_scan_applies_include_and_exclude_globs() {
+        let dir = temp_vault("globs");
+        let include = ScanOptions {
+            include: vec!["nested/**".to_string()],
+            exclude: vec![],
+        };
+        assert_eq!(scanned_names(dir.path(), &include), vec!["c"]);
+
+        let exclude = ScanOptions {
+            include: vec![],
+            exclude: vec!["a.md".to_string()],
+        };
+        assert_eq!(scanned_names(dir.path(), &exclude), vec!["b", "c"]);
+
+        let invalid = ScanOptions {
+            include: vec!["[".to_string()],
+            exclude: vec![],
+        };
+        let cancel = AtomicBool::new(false);
+        assert!(scan_files(
+            dir.path(),
+            &invalid,
+            &cancel,
+            &|_, _| {},
+            &parse_skill_file,
+            &mut |_| {}
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_scan_treats_skill_md_directory_as_bundle() {
+        let dir = temp_vault("bundle");
+        let bundle = dir.join("pdf-tools");
+        std::fs::create_dir_all(bundle.join("scripts")).unwrap();
+        std::fs::create_dir_all(bundle.join("references")).unwrap();
+        std::fs::write(bundle.join("SKILL.md"), "---\ntype: skill\n---\n# PDF").unwrap();
+        std::fs::write(bundle.join("scripts/fill.py"), "print()").unwrap();
+        std::fs::write(
+            bundle.join("references/forms.md"),
+            "---\nname: forms\n---\n",
+        )
+        .unwrap();
+
+        let cancel = AtomicBool::new(false);
+        let mut skills = Vec::new();
+        scan_files(
+            dir.path(),
+            &ScanOptions::default(),
+            &cancel,
+            &|_, _| {},
+            &parse_skill_file,
+            &mut |batch| skills.extend(batch),
+        )
+        .unwrap();
+
+        let names: Vec<_> = skills.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["a", "b", "c", "pdf-tools"]);
+        let resources = &skills[3].bundle.as_ref().unwrap().resources;
+        assert_eq!(resources.len(), 2);
+        assert_eq!(resources[1].path, "scripts/fill.py");
+    }
+
+    #[test]
+    fn test_index_file_cached_hit_and_update() {
+        let dir = temp_vault("cache");
+        let file = ScanCandidate {
+            path: dir.join("a.md"),
+            bundle: None,
+        };
+        let empty = ScanCache::load(dir.path(), None);
+
+        let FileOutcome::Parsed(first) = index_file_cached(&file, &empty) else {
+            panic!("expected parsed file");
+        };
+        assert_eq!(first.status, CacheStatus::Updated);
+
+        let mut cache = ScanCache::load(dir.path(), None);
+        cache.replace(BTreeMap::from([(first.key.clone(), first.entry)]), true);
+        let FileOutcome::Parsed(second) = index_file_cached(&file, &cache) else {
+            panic!("expected parsed file");
+        };
+        assert_eq!(second.status, CacheStatus::Hit);
+        assert_eq!(second.entry.skill.unwrap().name, "a");
+    }
+
+    #[test]
+    fn test_scan_skills_cancelled() {
+        let dir = temp_vault("cancel");
+        let cancel = AtomicBool::new(true);
+        let mut skills: Vec<SkillEntry> = Vec::new();
+        let outcome = scan_files(
+            dir.path(),
+            &ScanOptions::default(),
+            &cancel,
+            &|_, _| {},
+            &parse_skill_file,
+            &mut |batch| skills.extend(batch),
+        )
+        .unwrap();
+        assert!(skills.is_empty());
+        assert!(outcome.cancelled);
+    }
 }
@@ -11,8 +11,13 @@
 //!   - `git_diff` → unified-diff patch for one commit (or between two commits)
 
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
-use std::process::Command;
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use tauri::State;
+
+use crate::workspace::{check_relative_pathspec, WorkspaceRegistry};
 
 // ---------------------------------------------------------------------------
 // Public types
@@ -54,6 +59,10 @@ pub struct LogResult {
     /// True when repository has at least one commit reachable from `HEAD`.
     pub has_commit_history: bool,
     pub commits: Vec<CommitInfo>,
+    /// Set when the request itself was rejected (e.g. path outside the
+    /// open workspace).
+    #[serde(default)]
+    pub error: Option<String>,
 }
 
 // ---------------------------------------------------------------------------
@@ -69,8 +78,31 @@ pub struct LogResult {
 /// Returns `LogResult { is_git_repo: false, commits: [] }` when no git repo
 /// is found above `repo_path`.
 #[tauri::command]
-pub fn git_log(repo_path: String, subtree_path: Option<String>, limit: Option<usize>) -> LogResult {
-    let root = PathBuf::from(&repo_path);
+pub fn git_log(
+    workspaces: State<'_, WorkspaceRegistry>,
+    repo_path: String,
+    subtree_path: Option<String>,
+    limit: Option<usize>,
+) -> LogResult {
+    let checked = workspaces.resolve(&repo_path).and_then(|root| {
+        match subtree_path.as_deref() {
+            Some(sp) if !sp.is_empty() => check_relative_pathspec(sp)?,
+            _ => {}
+        }
+        Ok(root)
+    });
+    let root = match checked {
+        Ok(root) => root,
+        Err(e) => {
+            return LogResult {
+                is_git_repo: false,
+                is_unborn_repo: false,
+                has_commit_history: false,
+                commits: vec![],
+                error: Some(e),
+            }
+        }
+    };
 
     if !root.join(".git").is_dir() {
         return LogResult {
@@ -78,6 +110,7 @@ pub fn git_log(repo_path: String, subtree_path: Option<String>, limit: Option<us
             is_unborn_repo: false,
             has_commit_history: false,
             commits: vec![],
+            error: None,
         };
     }
 
@@ -94,6 +127,7 @@ pub fn git_log(repo_path: String, subtree_path: Option<String>, limit: Option<us
             is_unborn_repo: true,
             has_commit_history: false,
             commits: vec![],
+            error: None,
         };
     }
 
@@ -130,6 +164,7 @@ pub fn git_log(repo_path: String, subtree_path: Option<String>, limit: Option<us
                 is_unborn_repo: false,
                 has_commit_history,
                 commits: vec![],
+                error: None,
             };
         }
     };
@@ -141,6 +176,7 @@ pub fn git_log(repo_path: String, subtree_path: Option<String>, limit: Option<us
             is_unborn_repo: false,
             has_commit_history,
             commits: vec![],
+            error: None,
         };
     }
 
@@ -152,6 +188,7 @@ pub fn git_log(repo_path: String, subtree_path: Option<String>, limit: Option<us
         is_unborn_repo: false,
         has_commit_history,
         commits,
+        error: None,
     }
 }
 
@@ -163,12 +200,22 @@ pub fn git_log(repo_path: String, subtree_path: Option<String>, limit: Option<us
 /// `file_path`  — optional file filter (relative to repo root).
 #[tauri::command]
 pub fn git_diff(
+    workspaces: State<'_, WorkspaceRegistry>,
     repo_path: String,
     sha: String,
     sha2: Option<String>,
     file_path: Option<String>,
 ) -> DiffResult {
-    let root = PathBuf::from(&repo_path);
+    let root = match check_diff_request(&workspaces, &repo_path, &sha, &sha2, &file_path) {
+        Ok(root) => root,
+        Err(e) => {
+            return DiffResult {
+                patch: String::new(),
+                is_binary: false,
+                error: Some(e),
+            }
+        }
+    };
 
     if !root.join(".git").is_dir() {
         return DiffResult {
@@ -237,6 +284,98 @@ pub fn git_diff(
 // Internal helpers
 // ---------------------------------------------------------------------------
 
+/// Revisions become positional git arguments, so one starting with `-`
+/// would be parsed as an option (e.g. `--output=<file>`).
+fn check_revision(rev: &str) -> Result<(), String> {
+    if rev.is_empty() || rev.starts_with('-') || rev.contains("..") {
+        Err(format!("Invalid revision: {}", rev))
+    } else {
+        Ok(())
+    }
+}
+
+fn check_diff_request(
+    workspaces: &WorkspaceRegistry,
+    repo_path: &str,
+    sha: &str,
+    sha2: &Option<String>,
+    file_path: &Option<String>,
+) -> Result<PathBuf, String> {
+    let root = workspaces.resolve(repo_path)?;
+    check_revision(sha)?;
+    if let Some(s2) = sha2 {
+        check_revision(s2)?;
+    }
+    if let Some(fp) = file_path.as_deref().filter(|fp| !fp.is_empty()) {
+        check_relative_pathspec(fp)?;
+    }
+    Ok(root)
+}
+
+/// Unix time of the most recent commit on `HEAD` touching each of `wanted`
+/// (absolute paths), for those with history. Only history under `scope` is
+/// walked, and git is stopped as soon as every wanted file has been seen, so
+/// a large repository is not read to its first commit.
+pub(crate) fn last_commit_times(
+    repo_root: &Path,
+    scope: &Path,
+    wanted: &HashSet<PathBuf>,
+) -> HashMap<PathBuf, u64> {
+    let mut times = HashMap::new();
+    if wanted.is_empty() {
+        return times;
+    }
+    let pathspec = match scope.strip_prefix(repo_root) {
+        Ok(relative) if relative.as_os_str().is_empty() => ".".to_string(),
+        Ok(relative) => relative.to_string_lossy().to_string(),
+        Err(_) => return times,
+    };
+
+    let child = Command::new("git")
+        .current_dir(repo_root)
+        .args([
+            "--literal-pathspecs",
+            "-c",
+            "core.quotePath=false",
+            "log",
+            "HEAD",
+            "--name-only",
+            "--format=COMMIT_SEP %at",
+            "--",
+            &pathspec,
+        ])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn();
+    let Ok(mut child) = child else {
+        return times;
+    };
+
+    // Newest first, so the first commit seen for a path is its latest.
+    if let Some(stdout) = child.stdout.take() {
+        let mut timestamp = 0;
+        for line in BufReader::new(stdout).lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            if let Some(at) = line.strip_prefix("COMMIT_SEP ") {
+                timestamp = at.trim().parse().unwrap_or(0);
+                continue;
+            }
+            let path = repo_root.join(line.trim_end());
+            if !line.is_empty() && wanted.contains(&path) {
+                times.entry(path).or_insert(timestamp);
+                if times.len() == wanted.len() {
+                    break;
+                }
+            }
+        }
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+    times
+}
+
 /// Parse the output of `git log --name-only --format=COMMIT_SEP\n%H\n%at\n%aI\n%s\n`
 fn parse_git_log_output(text: &str) -> Vec<CommitInfo> {
     let mut commits = Vec::new();
@@ -1,7 +1,15 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use tauri::State;
 
 use crate::fs_scan::SkillEntry;
+use crate::graph_cycles::{condense, find_cycles, CondensedGraph, GraphCycle};
+use crate::graph_resolver::{MatchKind, Resolution, SkillResolver};
+use crate::graph_schema::{EdgeDirection, EdgeTarget, GraphSchema};
+use crate::graph_scripts::{orphan_scripts, resolve_file_target, FileInfo};
+use crate::skill_document::{parse_document, Span};
+use crate::workspace::WorkspaceRegistry;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GraphNode {
@@ -12,13 +20,50 @@ pub struct GraphNode {
     pub status: Option<String>,
     pub is_moc: bool,
     pub is_ghost: bool,
+    /// A file target that does not exist.
+    #[serde(default)]
+    pub is_broken: bool,
+    /// Set for file nodes such as scripts.
+    #[serde(default)]
+    pub file: Option<FileInfo>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GraphEdge {
     pub source: String,
     pub target: String,
-    pub kind: String, // "wiki", "related", "scripts"
+    pub kind: String, // "wiki", "related", "scripts", or a `.skills.toml` kind
+    /// Where a `wiki` edge's links appear in the source body, in order.
+    #[serde(default)]
+    pub spans: Vec<Span>,
+}
+
+/// A link whose target matched several skills; it points at a ghost node
+/// instead of one of them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AmbiguousLink {
+    pub source: String,
+    pub target: String,
+    pub kind: String,
+    pub candidates: Vec<String>,
+    pub matched_by: MatchKind,
+    /// Where the link appears in the body, for `wiki` links.
+    pub span: Option<Span>,
+}
+
+/// Files whose names normalize to the same node id.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdCollision {
+    pub id: String,
+    /// In path order; the first keeps `id`.
+    pub files: Vec<CollidingFile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollidingFile {
+    pub path: String,
+    pub name: String,
+    pub assigned_id: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,6 +72,21 @@ pub struct SkillGraph {
     pub edges: Vec<GraphEdge>,
     pub node_count: usize,
     pub edge_count: usize,
+    pub cycles: Vec<GraphCycle>,
+    #[serde(default)]
+    pub ambiguities: Vec<AmbiguousLink>,
+    #[serde(default)]
+    pub collisions: Vec<IdCollision>,
+    /// Renderer style hint per edge kind, from the edge schema.
+    #[serde(default)]
+    pub edge_styles: BTreeMap<String, String>,
+    /// Workspace-relative scripts no skill links to; only filled in by
+    /// `build_graph` with a workspace.
+    #[serde(default)]
+    pub orphan_scripts: Vec<String>,
+    /// The graph with each cycle collapsed to one node; only when requested.
+    #[serde(default)]
+    pub condensed: Option<CondensedGraph>,
 }
 
 fn get_str(fm: &serde_json::Value, key: &str) -> Option<String> {
@@ -37,18 +97,22 @@ fn get_bool(fm: &serde_json::Value, key: &str) -> bool {
     fm.get(key).and_then(|v| v.as_bool()).unwrap_or(false)
 }
 
-fn get_string_array(fm: &serde_json::Value, key: &str) -> Vec<String> {
-    fm.get(key)
-        .and_then(|v| v.as_array())
-        .map(|arr| {
-            arr.iter()
-                .filter_map(|v| v.as_str().map(|s| s.to_string()))
-                .collect()
-        })
-        .unwrap_or_default()
+/// String values of a link key: a list, or a single string.
+fn get_link_values(fm: &serde_json::Value, key: &str) -> Vec<String> {
+    match fm.get(key) {
+        Some(serde_json::Value::String(s)) => vec![s.clone()],
+        Some(serde_json::Value::Array(arr)) => arr
+            .iter()
+            .filter_map(|v| v.as_str().map(|s| s.to_string()))
+            .collect(),
+        _ => Vec::new(),
+    }
 }
 
-fn normalize_id(name: &str) -> String {
+/// Canonical node id for a skill name: lowercase, runs of non-alphanumerics
+/// collapsed to a single `-`. Anything that rewrites references must use this
+/// so it agrees with the graph on what a link points to.
+pub(crate) fn normalize_id(name: &str) -> String {
     name.trim()
         .to_lowercase()
         .chars()
@@ -60,23 +124,214 @@ fn normalize_id(name: &str) -> String {
         .join("-")
 }
 
+/// Node id for each `(path, name)`: `normalize_id(name)`, except that when
+/// several files share an id the one with the smallest path keeps it and the
+/// rest become `id--2`, `id--3`, … in path order. `normalize_id` never emits
+/// `--`, so a suffixed id cannot clash with another skill's name.
+pub(crate) fn assign_node_ids(entries: &[(&str, &str)]) -> (Vec<String>, Vec<IdCollision>) {
+    let mut ids: Vec<String> = entries.iter().map(|(_, name)| normalize_id(name)).collect();
+    let mut groups: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    for (i, id) in ids.iter().enumerate() {
+        if !id.is_empty() {
+            groups.entry(id.clone()).or_default().push(i);
+        }
+    }
+
+    let mut collisions = Vec::new();
+    for (id, mut members) in groups {
+        if members.len() < 2 {
+            continue;
+        }
+        members.sort_by(|&a, &b| entries[a].0.cmp(entries[b].0));
+        let mut files = Vec::new();
+        for (n, &i) in members.iter().enumerate() {
+            if n > 0 {
+                ids[i] = format!("{}--{}", id, n + 1);
+            }
+            files.push(CollidingFile {
+                path: entries[i].0.to_string(),
+                name: entries[i].1.to_string(),
+                assigned_id: ids[i].clone(),
+            });
+        }
+        collisions.push(IdCollision { id, files });
+    }
+
+    (ids, collisions)
+}
+
+/// `assign_node_ids` over the skills that become graph nodes: those with
+/// frontmatter and a non-empty id. Others get `None`.
+pub(crate) fn graph_node_ids(skills: &[SkillEntry]) -> (Vec<Option<String>>, Vec<IdCollision>) {
+    let graphed: Vec<usize> = (0..skills.len())
+        .filter(|&i| skills[i].frontmatter.is_some())
+        .collect();
+    let entries: Vec<(&str, &str)> = graphed
+        .iter()
+        .map(|&i| (skills[i].path.as_str(), skills[i].name.as_str()))
+        .collect();
+    let (ids, collisions) = assign_node_ids(&entries);
+
+    let mut node_ids: Vec<Option<String>> = vec![None; skills.len()];
+    for (i, id) in graphed.into_iter().zip(ids) {
+        if !id.is_empty() {
+            node_ids[i] = Some(id);
+        }
+    }
+    (node_ids, collisions)
+}
+
+/// Add a ghost node for a link target no skill defines, once.
+fn ensure_ghost(
+    nodes: &mut Vec<GraphNode>,
+    known_ids: &mut HashMap<String, bool>,
+    id: &str,
+    label: &str,
+) {
+    if known_ids.contains_key(id) {
+        return;
+    }
+    nodes.push(GraphNode {
+        id: id.to_string(),
+        label: label.to_string(),
+        node_type: "unresolved".to_string(),
+        category: None,
+        status: None,
+        is_moc: false,
+        is_ghost: true,
+        is_broken: false,
+        file: None,
+    });
+    known_ids.insert(id.to_string(), true);
+}
+
+/// Add a node for a file a skill links to, once, and return its id:
+/// `<node_type>:<path>`, labelled with the file name.
+fn ensure_file_node(
+    nodes: &mut Vec<GraphNode>,
+    known_ids: &mut HashMap<String, bool>,
+    node_type: &str,
+    path: &str,
+    info: FileInfo,
+) -> String {
+    let id = format!("{}:{}", node_type, path);
+    if !known_ids.contains_key(&id) {
+        nodes.push(GraphNode {
+            id: id.clone(),
+            label: path.rsplit('/').next().unwrap_or(path).to_string(),
+            node_type: node_type.to_string(),
+            category: None,
+            status: None,
+            is_moc: false,
+            is_ghost: false,
+            is_broken: !info.exists,
+            file: Some(info),
+        });
+        known_ids.insert(id.clone(), true);
+    }
+    id
+}
+
+/// Node id a link target, written in the skill at `source_path`, resolves
+/// to. Targets that match no skill, or several, get a ghost node in its own
+/// namespace so it never lands on a real skill: `ghost:<id>` for missing
+/// targets and `ambiguous:<id>` for ambiguous ones, whose candidates are
+/// returned.
+fn resolve_link(
+    resolver: &SkillResolver,
+    nodes: &mut Vec<GraphNode>,
+    known_ids: &mut HashMap<String, bool>,
+    target: &str,
+    source_path: &str,
+) -> (String, Option<(Vec<String>, MatchKind)>) {
+    let (id, ambiguity) = match resolver.resolve_from(target, source_path) {
+        Resolution::Found { id, .. } => return (id, None),
+        Resolution::Ambiguous {
+            candidates,
+            matched_by,
+        } => (
+            format!("ambiguous:{}", normalize_id(target)),
+            Some((candidates, matched_by)),
+        ),
+        Resolution::Missing => (format!("ghost:{}", normalize_id(target)), None),
+    };
+    ensure_ghost(nodes, known_ids, &id, target);
+    (id, ambiguity)
+}
+
+/// Build the graph with the edge schema of `workspace_root`, or with the
+/// built-in schema when no workspace is given.
+pub(crate) fn load_skill_graph(
+    skills: &[SkillEntry],
+    workspaces: &WorkspaceRegistry,
+    workspace_root: Option<&str>,
+) -> Result<SkillGraph, String> {
+    let Some(root) = workspace_root else {
+        return Ok(build_skill_graph(skills));
+    };
+    let root = workspaces.resolve(root)?;
+    Ok(build_workspace_graph(
+        skills,
+        &GraphSchema::load(&root)?,
+        Some(&root),
+    ))
+}
+
+/// Scripts under `root` that no script node of `graph` resolved to. This
+/// walks the whole workspace, so only `build_graph` asks for it.
+fn unreferenced_scripts(graph: &SkillGraph, root: &Path) -> Result<Vec<String>, String> {
+    let referenced: HashSet<PathBuf> = graph
+        .nodes
+        .iter()
+        .filter(|node| node.node_type == "script")
+        .filter_map(|node| node.file.as_ref()?.resolved_path.as_ref())
+        .map(PathBuf::from)
+        .collect();
+    orphan_scripts(root, &referenced)
+}
+
+/// `workspace_root` — the open workspace, whose `.skills.toml` may map more
+/// frontmatter keys to edges.
 #[tauri::command]
-pub async fn build_graph(skills: Vec<SkillEntry>) -> Result<SkillGraph, String> {
+pub async fn build_graph(
+    workspaces: State<'_, WorkspaceRegistry>,
+    skills: Vec<SkillEntry>,
+    workspace_root: Option<String>,
+    condense_cycles: Option<bool>,
+) -> Result<SkillGraph, String> {
+    let mut graph = load_skill_graph(&skills, &workspaces, workspace_root.as_deref())?;
+    if let Some(root) = workspace_root.as_deref() {
+        graph.orphan_scripts = unreferenced_scripts(&graph, &workspaces.resolve(root)?)?;
+    }
+    if condense_cycles.unwrap_or(false) {
+        graph.condensed = Some(condense(&graph.nodes, &graph.edges, &graph.cycles));
+    }
+    Ok(graph)
+}
+
+pub fn build_skill_graph(skills: &[SkillEntry]) -> SkillGraph {
+    build_workspace_graph(skills, &GraphSchema::default(), None)
+}
+
+/// File targets are looked up relative to each skill and then `root`, and
+/// only inside it; without a root they are all reported broken.
+pub fn build_workspace_graph(
+    skills: &[SkillEntry],
+    schema: &GraphSchema,
+    root: Option<&Path>,
+) -> SkillGraph {
     let mut nodes: Vec<GraphNode> = Vec::new();
     let mut edges: Vec<GraphEdge> = Vec::new();
     let mut known_ids: HashMap<String, bool> = HashMap::new();
+    let mut ambiguities: Vec<AmbiguousLink> = Vec::new();
+    let (node_ids, collisions) = graph_node_ids(skills);
+    let resolver = SkillResolver::new(skills, &node_ids);
 
     // First pass: create nodes
-    for skill in &skills {
-        let fm = match &skill.frontmatter {
-            Some(fm) => fm,
-            None => continue,
-        };
-
-        let id = normalize_id(&skill.name);
-        if id.is_empty() {
+    for (skill, id) in skills.iter().zip(&node_ids) {
+        let (Some(fm), Some(id)) = (&skill.frontmatter, id) else {
             continue;
-        }
+        };
 
         let node_type = get_str(fm, "type").unwrap_or_else(|| "skill".to_string());
         let is_moc = get_bool(fm, "moc");
@@ -89,87 +344,290 @@ pub async fn build_graph(skills: Vec<SkillEntry>) -> Result<SkillGraph, String>
             status: get_str(fm, "status"),
             is_moc,
             is_ghost: false,
+            is_broken: false,
+            file: None,
         });
 
-        known_ids.insert(id, true);
+        known_ids.insert(id.clone(), true);
     }
 
     // Second pass: create edges
-    for skill in &skills {
-        let fm = match &skill.frontmatter {
-            Some(fm) => fm,
-            None => continue,
-        };
-
-        let source_id = normalize_id(&skill.name);
-        if source_id.is_empty() {
+    for (skill, source_id) in skills.iter().zip(&node_ids) {
+        let (Some(fm), Some(source_id)) = (&skill.frontmatter, source_id) else {
             continue;
-        }
+        };
 
-        // related[] → directed edges
-        for target_name in get_string_array(fm, "related") {
-            let target_id = normalize_id(&target_name);
-            if target_id.is_empty() {
-                continue;
+        // Mapped frontmatter keys (`related`, `scripts`, `.skills.toml`)
+        // → typed edges
+        for (key, edge_schema) in &schema.edges {
+            let kind = edge_schema.kind_for(key);
+            let mut values = get_link_values(fm, key);
+            // Bundled scripts count as listed
+            if key == "scripts" {
+                values.extend(
+                    skill
+                        .bundle
+                        .iter()
+                        .flat_map(|bundle| bundle.script_paths().map(|p| p.to_string())),
+                );
             }
 
-            // Create ghost node if target unknown
-            if !known_ids.contains_key(&target_id) {
-                nodes.push(GraphNode {
-                    id: target_id.clone(),
-                    label: target_name,
-                    node_type: "unresolved".to_string(),
-                    category: None,
-                    status: None,
-                    is_moc: false,
-                    is_ghost: true,
+            let mut linked: Vec<String> = Vec::new();
+            for value in values {
+                let target_id = match edge_schema.target {
+                    EdgeTarget::Skill => {
+                        if normalize_id(&value).is_empty() {
+                            continue;
+                        }
+                        let (target_id, ambiguity) = resolve_link(
+                            &resolver,
+                            &mut nodes,
+                            &mut known_ids,
+                            &value,
+                            &skill.path,
+                        );
+                        if let Some((candidates, matched_by)) = ambiguity {
+                            ambiguities.push(AmbiguousLink {
+                                source: source_id.clone(),
+                                target: value.clone(),
+                                kind: kind.to_string(),
+                                candidates,
+                                matched_by,
+                                span: None,
+                            });
+                        }
+                        target_id
+                    }
+                    EdgeTarget::File => {
+                        let path = value.trim().trim_start_matches("./");
+                        if path.is_empty() {
+                            continue;
+                        }
+                        let (path, info) = resolve_file_target(path, &skill.path, root);
+                        ensure_file_node(
+                            &mut nodes,
+                            &mut known_ids,
+                            edge_schema.file_node_type(),
+                            &path,
+                            info,
+                        )
+                    }
+                };
+                if linked.contains(&target_id) {
+                    continue;
+                }
+                linked.push(target_id.clone());
+
+                let (source, target) = match edge_schema.direction {
+                    EdgeDirection::Outgoing => (source_id.clone(), target_id),
+                    EdgeDirection::Incoming => (target_id, source_id.clone()),
+                };
+                edges.push(GraphEdge {
+                    source,
+                    target,
+                    kind: kind.to_string(),
+                    spans: Vec::new(),
                 });
-                known_ids.insert(target_id.clone(), true);
             }
-
-            edges.push(GraphEdge {
-                source: source_id.clone(),
-                target: target_id,
-                kind: "related".to_string(),
-            });
         }
 
-        // scripts[] → script edges
-        for script_path in get_string_array(fm, "scripts") {
-            let script_id = format!("script:{}", script_path.trim());
-
-            if !known_ids.contains_key(&script_id) {
-                nodes.push(GraphNode {
-                    id: script_id.clone(),
-                    label: script_path
-                        .rsplit('/')
-                        .next()
-                        .unwrap_or(&script_path)
-                        .to_string(),
-                    node_type: "script".to_string(),
-                    category: None,
-                    status: None,
-                    is_moc: false,
-                    is_ghost: false,
+        // [[wiki-links]] in the body → one wiki edge per target, with the
+        // span of every link to it
+        let mut wiki_edges: Vec<GraphEdge> = Vec::new();
+        for link in parse_document(&skill.body).wiki_links {
+            if normalize_id(&link.target).is_empty() {
+                continue;
+            }
+            let (target_id, ambiguity) = resolve_link(
+                &resolver,
+                &mut nodes,
+                &mut known_ids,
+                &link.target,
+                &skill.path,
+            );
+            if let Some((candidates, matched_by)) = ambiguity {
+                ambiguities.push(AmbiguousLink {
+                    source: source_id.clone(),
+                    target: link.target.clone(),
+                    kind: "wiki".to_string(),
+                    candidates,
+                    matched_by,
+                    span: Some(link.span),
                 });
-                known_ids.insert(script_id.clone(), true);
             }
 
-            edges.push(GraphEdge {
-                source: source_id.clone(),
-                target: script_id,
-                kind: "scripts".to_string(),
-            });
+            match wiki_edges.iter_mut().find(|edge| edge.target == target_id) {
+                Some(edge) => edge.spans.push(link.span),
+                None => wiki_edges.push(GraphEdge {
+                    source: source_id.clone(),
+                    target: target_id,
+                    kind: "wiki".to_string(),
+                    spans: vec![link.span],
+                }),
+            }
         }
+        edges.extend(wiki_edges);
     }
 
     let node_count = nodes.len();
     let edge_count = edges.len();
+    let cycles = find_cycles(&nodes, &edges);
 
-    Ok(SkillGraph {
+    SkillGraph {
         nodes,
         edges,
         node_count,
         edge_count,
-    })
+        cycles,
+        ambiguities,
+        collisions,
+        edge_styles: schema.edge_styles(),
+        orphan_scripts: Vec::new(),
+        condensed: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{skill, skill_entry};
+
+    #[test]
+    fn test_body_wiki_links_become_edges() {
+        let body = "Use [[py-pandas]] first.\n\n```\n[[in-code]]\n```\nThen [[Py Pandas|again]] and [[missing]].\n";
+        let graph = build_skill_graph(&[
+            SkillEntry {
+                body: body.to_string(),
+                ..skill("loader", serde_json::json!({"related": ["py-pandas"]}))
+            },
+            skill("py-pandas", serde_json::json!({})),
+        ]);
+
+        let wiki: Vec<&GraphEdge> = graph.edges.iter().filter(|e| e.kind == "wiki").collect();
+        assert_eq!(wiki.len(), 2);
+        assert_eq!(wiki[0].target, "py-pandas");
+        let lines: Vec<usize> = wiki[0].spans.iter().map(|s| s.line).collect();
+        assert_eq!(lines, vec![1, 6]);
+        assert_eq!(
+            &body[wiki[0].spans[0].start..wiki[0].spans[0].end],
+            "[[py-pandas]]"
+        );
+
+        let ghost = graph
+            .nodes
+            .iter()
+            .find(|n| n.id == "ghost:missing")
+            .unwrap();
+        assert!(ghost.is_ghost);
+        assert!(!graph.nodes.iter().any(|n| n.id == "in-code"));
+
+        // A missing path never lands on the real skill its id resembles;
+        // relative paths start from the linking skill
+        let graph = build_skill_graph(&[
+            SkillEntry {
+                body: "[[archive/py-pandas]] [[./py-pandas]]".to_string(),
+                ..skill("loader", serde_json::json!({}))
+            },
+            skill("py-pandas", serde_json::json!({})),
+        ]);
+        let targets: Vec<&str> = graph.edges.iter().map(|e| e.target.as_str()).collect();
+        assert_eq!(targets.len(), 2);
+        assert!(targets[0].starts_with("ghost:"));
+        assert_eq!(targets[1], "py-pandas");
+    }
+
+    #[test]
+    fn test_colliding_names_get_distinct_ids() {
+        let first = skill_entry(
+            "/ws/b/Data Analysis.md",
+            "Data Analysis",
+            serde_json::json!({}),
+        );
+        let second = skill_entry(
+            "/ws/a/data-analysis.md",
+            "data-analysis",
+            serde_json::json!({}),
+        );
+        let linker = skill("report", serde_json::json!({"related": ["data analysis"]}));
+
+        let graph = build_skill_graph(&[first, second, linker]);
+        let ids: Vec<&str> = graph.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids[..2], ["data-analysis--2", "data-analysis"]);
+
+        assert_eq!(graph.collisions.len(), 1);
+        let assigned: Vec<(&str, &str)> = graph.collisions[0]
+            .files
+            .iter()
+            .map(|f| (f.path.as_str(), f.assigned_id.as_str()))
+            .collect();
+        assert_eq!(
+            assigned,
+            vec![
+                ("/ws/a/data-analysis.md", "data-analysis"),
+                ("/ws/b/Data Analysis.md", "data-analysis--2"),
+            ]
+        );
+        let related = graph.edges.iter().find(|e| e.kind == "related").unwrap();
+        assert_eq!(related.target, "ambiguous:data-analysis");
+        assert_eq!(graph.ambiguities.len(), 1);
+        assert_eq!(
+            graph.ambiguities[0].candidates,
+            vec!["data-analysis", "data-analysis--2"]
+        );
+    }
+
+    #[test]
+    fn test_schema_maps_frontmatter_keys_to_edges() {
+        let schema = GraphSchema::from_toml(
+            "[graph.edges.depends_on]\nkind = \"depends\"\n\n\
+             [graph.edges.supersedes]\ndirection = \"incoming\"\n\n\
+             [graph.edges.docs]\ntarget = \"file\"\nnode_type = \"doc\"\n",
+        )
+        .unwrap();
+        let graph = build_workspace_graph(
+            &[
+                skill(
+                    "loader",
+                    serde_json::json!({
+                        "depends_on": ["pandas", "pandas"],
+                        "supersedes": "old-loader",
+                        "docs": ["./docs/loader.md"],
+                        "scripts": ["run.sh"],
+                    }),
+                ),
+                skill("pandas", serde_json::json!({})),
+            ],
+            &schema,
+            None,
+        );
+
+        let edges: Vec<(&str, &str, &str)> = graph
+            .edges
+            .iter()
+            .map(|e| (e.source.as_str(), e.target.as_str(), e.kind.as_str()))
+            .collect();
+        assert_eq!(
+            edges,
+            vec![
+                ("loader", "pandas", "depends"),
+                ("loader", "doc:docs/loader.md", "docs"),
+                ("loader", "script:run.sh", "scripts"),
+                ("ghost:old-loader", "loader", "supersedes"),
+            ]
+        );
+        let doc = graph
+            .nodes
+            .iter()
+            .find(|n| n.id == "doc:docs/loader.md")
+            .unwrap();
+        assert_eq!(
+            (doc.label.as_str(), doc.node_type.as_str()),
+            ("loader.md", "doc")
+        );
+        assert!(graph
+            .nodes
+            .iter()
+            .any(|n| n.id == "ghost:old-loader" && n.is_ghost));
+        assert_eq!(graph.edge_styles["scripts"], "dotted");
+    }
 }
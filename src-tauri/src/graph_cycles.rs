@@ -0,0 +1,300 @@
+//! graph_cycles.rs — Cycle detection and condensation for the skill graph.
+//!
+//! Strongly connected components are found with Tarjan's algorithm over
+//! skill and MOC nodes; ghosts and scripts have no outgoing links, so they
+//! can never close a loop. A component is a cycle when it has more than one
+//! member or its single member links to itself.
+//!
+//! Cycles are numbered `cycle:1`, `cycle:2`, … in order of their smallest
+//! member id, so the same graph always yields the same ids. The condensed
+//! graph replaces each cycle with one super-node of type `cycle` and is a DAG.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use crate::graph_builder::{GraphEdge, GraphNode};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphCycle {
+    pub id: String,
+    /// Member node ids, sorted.
+    pub members: Vec<String>,
+    /// Every edge between two members, self-loops included.
+    pub edges: Vec<GraphEdge>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CondensedGraph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+    /// Member node id → id of the cycle super-node that replaced it.
+    pub cycle_of: HashMap<String, String>,
+}
+
+fn can_cycle(node: &GraphNode) -> bool {
+    !node.is_ghost && node.node_type != "script"
+}
+
+/// Tarjan's SCC over `adjacency`, iterative so deep link chains cannot
+/// overflow the stack. Returns components as lists of vertex indices.
+fn tarjan_scc(adjacency: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    let n = adjacency.len();
+    let mut index: Vec<Option<usize>> = vec![None; n];
+    let mut low_link = vec![0usize; n];
+    let mut on_stack = vec![false; n];
+    let mut stack: Vec<usize> = Vec::new();
+    let mut components: Vec<Vec<usize>> = Vec::new();
+    let mut next_index = 0;
+
+    for start in 0..n {
+        if index[start].is_some() {
+            continue;
+        }
+        index[start] = Some(next_index);
+        low_link[start] = next_index;
+        next_index += 1;
+        stack.push(start);
+        on_stack[start] = true;
+
+        // (vertex, position of the next neighbour to visit)
+        let mut calls: Vec<(usize, usize)> = vec![(start, 0)];
+        while let Some(&(v, i)) = calls.last() {
+            if let Some(&w) = adjacency[v].get(i) {
+                calls.last_mut().unwrap().1 += 1;
+                match index[w] {
+                    None => {
+                        index[w] = Some(next_index);
+                        low_link[w] = next_index;
+                        next_index += 1;
+                        stack.push(w);
+                        on_stack[w] = true;
+                        calls.push((w, 0));
+                    }
+                    Some(w_index) if on_stack[w] => {
+                        low_link[v] = low_link[v].min(w_index);
+                    }
+                    Some(_) => {}
+                }
+                continue;
+            }
+
+            calls.pop();
+            if let Some(&(parent, _)) = calls.last() {
+                low_link[parent] = low_link[parent].min(low_link[v]);
+            }
+            if Some(low_link[v]) == index[v] {
+                let mut component = Vec::new();
+                while let Some(member) = stack.pop() {
+                    on_stack[member] = false;
+                    component.push(member);
+                    if member == v {
+                        break;
+                    }
+                }
+                components.push(component);
+            }
+        }
+    }
+
+    components
+}
+
+/// Find every cycle in the graph.
+pub fn find_cycles(nodes: &[GraphNode], edges: &[GraphEdge]) -> Vec<GraphCycle> {
+    let eligible: Vec<&str> = nodes
+        .iter()
+        .filter(|node| can_cycle(node))
+        .map(|node| node.id.as_str())
+        .collect();
+    let position: HashMap<&str, usize> = eligible
+        .iter()
+        .enumerate()
+        .map(|(i, id)| (*id, i))
+        .collect();
+
+    let mut adjacency: Vec<Vec<usize>> = vec![Vec::new(); eligible.len()];
+    let mut self_loops: HashSet<usize> = HashSet::new();
+    for edge in edges {
+        let (Some(&source), Some(&target)) = (
+            position.get(edge.source.as_str()),
+            position.get(edge.target.as_str()),
+        ) else {
+            continue;
+        };
+        if source == target {
+            self_loops.insert(source);
+        }
+        adjacency[source].push(target);
+    }
+
+    let mut member_sets: Vec<Vec<String>> = tarjan_scc(&adjacency)
+        .into_iter()
+        .filter(|component| component.len() > 1 || self_loops.contains(&component[0]))
+        .map(|component| {
+            let mut members: Vec<String> = component
+                .into_iter()
+                .map(|i| eligible[i].to_string())
+                .collect();
+            members.sort();
+            members
+        })
+        .collect();
+    member_sets.sort();
+
+    member_sets
+        .into_iter()
+        .enumerate()
+        .map(|(i, members)| {
+            let member_set: HashSet<&str> = members.iter().map(|m| m.as_str()).collect();
+            let cycle_edges = edges
+                .iter()
+                .filter(|edge| {
+                    member_set.contains(edge.source.as_str())
+                        && member_set.contains(edge.target.as_str())
+                })
+                .cloned()
+                .collect();
+            GraphCycle {
+                id: format!("cycle:{}", i + 1),
+                members,
+                edges: cycle_edges,
+            }
+        })
+        .collect()
+}
+
+/// Collapse each cycle into a single `cycle` node. Edges are re-pointed at
+/// the super-nodes, edges inside a cycle are dropped, and parallel edges of
+/// the same kind are merged; merged edges carry no spans since they may come
+/// from different bodies.
+pub fn condense(nodes: &[GraphNode], edges: &[GraphEdge], cycles: &[GraphCycle]) -> CondensedGraph {
+    let mut cycle_of: HashMap<String, String> = HashMap::new();
+    for cycle in cycles {
+        for member in &cycle.members {
+            cycle_of.insert(member.clone(), cycle.id.clone());
+        }
+    }
+
+    let mut condensed_nodes: Vec<GraphNode> = nodes
+        .iter()
+        .filter(|node| !cycle_of.contains_key(&node.id))
+        .cloned()
+        .collect();
+    condensed_nodes.extend(cycles.iter().map(|cycle| GraphNode {
+        id: cycle.id.clone(),
+        label: format!("cycle({})", cycle.members.len()),
+        node_type: "cycle".to_string(),
+        category: None,
+        status: None,
+        is_moc: false,
+        is_ghost: false,
+        is_broken: false,
+        file: None,
+    }));
+
+    let mut condensed_edges: Vec<GraphEdge> = Vec::new();
+    let mut seen: HashSet<(String, String, String)> = HashSet::new();
+    for edge in edges {
+        let source = cycle_of.get(&edge.source).unwrap_or(&edge.source);
+        let target = cycle_of.get(&edge.target).unwrap_or(&edge.target);
+        if source == target && cycle_of.contains_key(&edge.source) {
+            continue;
+        }
+        let collapsed = source != &edge.source || target != &edge.target;
+        if !seen.insert((source.clone(), target.clone(), edge.kind.clone())) {
+            continue;
+        }
+        condensed_edges.push(GraphEdge {
+            source: source.clone(),
+            target: target.clone(),
+            kind: edge.kind.clone(),
+            spans: if collapsed {
+                Vec::new()
+            } else {
+                edge.spans.clone()
+            },
+        });
+    }
+
+    CondensedGraph {
+        nodes: condensed_nodes,
+        edges: condensed_edges,
+        cycle_of,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(id: &str) -> GraphNode {
+        GraphNode {
+            id: id.to_string(),
+            label: id.to_string(),
+            node_type: "skill".to_string(),
+            category: None,
+            status: None,
+            is_moc: false,
+            is_ghost: false,
+            is_broken: false,
+            file: None,
+        }
+    }
+
+    fn edge(source: &str, target: &str) -> GraphEdge {
+        GraphEdge {
+            source: source.to_string(),
+            target: target.to_string(),
+            kind: "related".to_string(),
+            spans: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_find_and_condense_cycles() {
+        let nodes: Vec<GraphNode> = ["a", "b", "c", "d", "e"]
+            .iter()
+            .map(|id| node(id))
+            .collect();
+        let edges = vec![
+            edge("a", "b"),
+            edge("b", "c"),
+            edge("c", "a"),
+            edge("c", "d"),
+            edge("d", "d"),
+            edge("b", "e"),
+            edge("a", "e"),
+        ];
+
+        let cycles = find_cycles(&nodes, &edges);
+        assert_eq!(cycles.len(), 2);
+        assert_eq!(cycles[0].id, "cycle:1");
+        assert_eq!(cycles[0].members, vec!["a", "b", "c"]);
+        assert_eq!(cycles[0].edges.len(), 3);
+        assert_eq!(cycles[1].members, vec!["d"]);
+
+        let condensed = condense(&nodes, &edges, &cycles);
+        let ids: Vec<&str> = condensed.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, vec!["e", "cycle:1", "cycle:2"]);
+        let pairs: Vec<(&str, &str)> = condensed
+            .edges
+            .iter()
+            .map(|e| (e.source.as_str(), e.target.as_str()))
+            .collect();
+        assert_eq!(pairs, vec![("cycle:1", "cycle:2"), ("cycle:1", "e")]);
+        assert!(find_cycles(&condensed.nodes, &condensed.edges).is_empty());
+    }
+
+    #[test]
+    fn test_deep_chain_does_not_overflow() {
+        let nodes: Vec<GraphNode> = (0..50_000).map(|i| node(&i.to_string())).collect();
+        let mut edges: Vec<GraphEdge> = (1..50_000)
+            .map(|i| edge(&(i - 1).to_string(), &i.to_string()))
+            .collect();
+        edges.push(edge("49999", "0"));
+
+        let cycles = find_cycles(&nodes, &edges);
+        assert_eq!(cycles.len(), 1);
+        assert_eq!(cycles[0].members.len(), 50_000);
+    }
+}
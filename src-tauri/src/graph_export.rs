@@ -0,0 +1,568 @@
+//! graph_export.rs — Serialize the skill graph for other tools.
+//!
+//! Formats: Graphviz `dot`, `graphml`, Mermaid `mermaid` (a `flowchart`) and
+//! JSON Graph Format `jgf`. Node type, category, status, MOC, ghost and broken flags
+//! and the edge kind are carried as attributes where the format has them and
+//! as shapes, classes or line styles where it does not.
+//!
+//! An optional filter narrows the export to a subgraph; edges are kept only
+//! when both ends survive it.
+//!
+//! Tauri commands exposed:
+//!   - `export_graph` → the serialized graph as a string
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use tauri::State;
+
+use crate::fs_scan::SkillEntry;
+use crate::graph_builder::{load_skill_graph, GraphEdge, GraphNode, SkillGraph};
+use crate::graph_query::{dependencies, GraphIndex};
+use crate::workspace::WorkspaceRegistry;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Dot,
+    Graphml,
+    Mermaid,
+    Jgf,
+}
+
+/// Which part of the graph to export. Empty lists allow everything.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExportFilter {
+    #[serde(default)]
+    pub node_types: Vec<String>,
+    #[serde(default)]
+    pub categories: Vec<String>,
+    #[serde(default)]
+    pub statuses: Vec<String>,
+    #[serde(default)]
+    pub edge_kinds: Vec<String>,
+    #[serde(default)]
+    pub exclude_ghosts: bool,
+    /// Only this node and what it reaches, within `max_depth` hops.
+    #[serde(default)]
+    pub root: Option<String>,
+    #[serde(default)]
+    pub max_depth: Option<usize>,
+}
+
+// ---------------------------------------------------------------------------
+// Filtering
+// ---------------------------------------------------------------------------
+
+fn allows(list: &[String], value: Option<&str>) -> bool {
+    list.is_empty() || value.is_some_and(|v| list.iter().any(|item| item.eq_ignore_ascii_case(v)))
+}
+
+/// The subgraph selected by `filter`, or an error if its root is unknown.
+pub fn filter_graph(graph: &SkillGraph, filter: &ExportFilter) -> Result<SkillGraph, String> {
+    let reachable: Option<HashSet<String>> = match &filter.root {
+        Some(root) => {
+            let mut ids: HashSet<String> = dependencies(graph, root, filter.max_depth)?
+                .into_iter()
+                .map(|reached| reached.node.id)
+                .collect();
+            let index = GraphIndex::new(graph);
+            ids.insert(index.node(index.find(root)?).id.clone());
+            Some(ids)
+        }
+        None => None,
+    };
+
+    let nodes: Vec<GraphNode> = graph
+        .nodes
+        .iter()
+        .filter(|node| {
+            allows(&filter.node_types, Some(&node.node_type))
+                && allows(&filter.categories, node.category.as_deref())
+                && allows(&filter.statuses, node.status.as_deref())
+                && !(filter.exclude_ghosts && node.is_ghost)
+                && reachable.as_ref().is_none_or(|ids| ids.contains(&node.id))
+        })
+        .cloned()
+        .collect();
+    let kept: HashSet<&str> = nodes.iter().map(|node| node.id.as_str()).collect();
+    let edges: Vec<GraphEdge> = graph
+        .edges
+        .iter()
+        .filter(|edge| {
+            kept.contains(edge.source.as_str())
+                && kept.contains(edge.target.as_str())
+                && allows(&filter.edge_kinds, Some(&edge.kind))
+        })
+        .cloned()
+        .collect();
+
+    Ok(SkillGraph {
+        node_count: nodes.len(),
+        edge_count: edges.len(),
+        cycles: Vec::new(),
+        ambiguities: Vec::new(),
+        collisions: Vec::new(),
+        edge_styles: graph.edge_styles.clone(),
+        orphan_scripts: graph.orphan_scripts.clone(),
+        condensed: None,
+        nodes,
+        edges,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Graphviz DOT
+// ---------------------------------------------------------------------------
+
+fn dot_quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Shape and style; shapes follow the Cytoscape view (`TYPE_TO_SHAPE` in
+/// packages/core); ghosts and broken file nodes are dashed.
+fn dot_node_style(node: &GraphNode) -> (&'static str, &'static str) {
+    if node.is_ghost {
+        return ("box", "rounded,dashed");
+    }
+    if node.is_broken {
+        return ("box", "dashed");
+    }
+    match node.node_type.as_str() {
+        "subagent" | "unresolved" | "cycle" => ("box", "rounded"),
+        "hook" => ("hexagon", "solid"),
+        "command" => ("parallelogram", "solid"),
+        "moc" => ("diamond", "solid"),
+        "script" => ("box", "solid"),
+        _ => ("ellipse", "solid"),
+    }
+}
+
+/// The schema's style hint for the edge kind, if the exporters know it.
+fn edge_style<'a>(graph: &'a SkillGraph, kind: &str) -> &'a str {
+    match graph.edge_styles.get(kind).map(|style| style.as_str()) {
+        Some(style @ ("solid" | "dashed" | "dotted" | "bold")) => style,
+        _ => "solid",
+    }
+}
+
+fn to_dot(graph: &SkillGraph) -> String {
+    let mut out = String::from("digraph skills {\n  rankdir=LR;\n");
+    for node in &graph.nodes {
+        let (shape, style) = dot_node_style(node);
+        let _ = write!(
+            out,
+            "  {} [label={}, shape={}, style=\"{}\", node_type={}, is_moc={}, is_ghost={}, is_broken={}",
+            dot_quote(&node.id),
+            dot_quote(&node.label),
+            shape,
+            style,
+            dot_quote(&node.node_type),
+            node.is_moc,
+            node.is_ghost,
+            node.is_broken,
+        );
+        if let Some(category) = &node.category {
+            let _ = write!(out, ", category={}", dot_quote(category));
+        }
+        if let Some(status) = &node.status {
+            let _ = write!(out, ", status={}", dot_quote(status));
+        }
+        out.push_str("];\n");
+    }
+    for edge in &graph.edges {
+        let _ = writeln!(
+            out,
+            "  {} -> {} [kind={}, style={}];",
+            dot_quote(&edge.source),
+            dot_quote(&edge.target),
+            dot_quote(&edge.kind),
+            edge_style(graph, &edge.kind),
+        );
+    }
+    out.push_str("}\n");
+    out
+}
+
+// ---------------------------------------------------------------------------
+// GraphML
+// ---------------------------------------------------------------------------
+
+fn xml_escape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// `(id, for, attr.type)` of each GraphML attribute.
+const GRAPHML_KEYS: [(&str, &str, &str); 8] = [
+    ("label", "node", "string"),
+    ("node_type", "node", "string"),
+    ("category", "node", "string"),
+    ("status", "node", "string"),
+    ("is_moc", "node", "boolean"),
+    ("is_ghost", "node", "boolean"),
+    ("is_broken", "node", "boolean"),
+    ("kind", "edge", "string"),
+];
+
+fn to_graphml(graph: &SkillGraph) -> String {
+    let mut out = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+    );
+    for (id, target, kind) in GRAPHML_KEYS {
+        let _ = writeln!(
+            out,
+            "  <key id=\"{0}\" for=\"{1}\" attr.name=\"{0}\" attr.type=\"{2}\"/>",
+            id, target, kind
+        );
+    }
+    out.push_str("  <graph id=\"skills\" edgedefault=\"directed\">\n");
+    for node in &graph.nodes {
+        let _ = writeln!(out, "    <node id=\"{}\">", xml_escape(&node.id));
+        let mut data = |key: &str, value: &str| {
+            let _ = writeln!(
+                out,
+                "      <data key=\"{}\">{}</data>",
+                key,
+                xml_escape(value)
+            );
+        };
+        data("label", &node.label);
+        data("node_type", &node.node_type);
+        if let Some(category) = &node.category {
+            data("category", category);
+        }
+        if let Some(status) = &node.status {
+            data("status", status);
+        }
+        data("is_moc", if node.is_moc { "true" } else { "false" });
+        data("is_ghost", if node.is_ghost { "true" } else { "false" });
+        data("is_broken", if node.is_broken { "true" } else { "false" });
+        out.push_str("    </node>\n");
+    }
+    for (i, edge) in graph.edges.iter().enumerate() {
+        let _ = writeln!(
+            out,
+            "    <edge id=\"e{}\" source=\"{}\" target=\"{}\">\n      <data key=\"kind\">{}</data>\n    </edge>",
+            i,
+            xml_escape(&edge.source),
+            xml_escape(&edge.target),
+            xml_escape(&edge.kind),
+        );
+    }
+    out.push_str("  </graph>\n</graphml>\n");
+    out
+}
+
+// ---------------------------------------------------------------------------
+// Mermaid
+// ---------------------------------------------------------------------------
+
+/// Mermaid ids must be plain identifiers, so nodes are numbered and the
+/// real id goes in the label. Labels are quoted; `#` and `"` become entity
+/// codes so they cannot end the label or start one.
+fn mermaid_label(value: &str) -> String {
+    value.replace('#', "#35;").replace('"', "#quot;")
+}
+
+/// Ghosts and broken file nodes get their own class; other nodes are
+/// classed by type.
+fn mermaid_class(node: &GraphNode) -> String {
+    if node.is_ghost {
+        return "ghost".to_string();
+    }
+    if node.is_broken {
+        return "broken".to_string();
+    }
+    let class: String = node
+        .node_type
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect();
+    if class.is_empty() {
+        "skill".to_string()
+    } else {
+        class
+    }
+}
+
+/// Styles follow the Cytoscape view (`TYPE_TO_COLOR` in packages/core);
+/// types it does not know look like skills.
+fn mermaid_class_style(class: &str) -> &'static str {
+    match class {
+        "ghost" => "fill:#708090,stroke:#708090,stroke-dasharray:5 5,color:#FFFFFF",
+        "broken" => "fill:#708090,stroke:#FF5722,stroke-dasharray:5 5,color:#FFFFFF",
+        "subagent" => "fill:#6495ED,color:#FFFFFF",
+        "hook" => "fill:#4682B4,color:#FFFFFF",
+        "command" => "fill:#66B2DD,color:#FFFFFF",
+        "moc" | "cycle" => "fill:#F7931A,color:#FFFFFF",
+        "script" | "unresolved" => "fill:#708090,color:#FFFFFF",
+        _ => "fill:#0076B6,color:#FFFFFF",
+    }
+}
+
+/// Mermaid has no separate dotted and dashed lines.
+fn mermaid_arrow(graph: &SkillGraph, kind: &str) -> &'static str {
+    match edge_style(graph, kind) {
+        "dashed" | "dotted" => "-.->",
+        "bold" => "==>",
+        _ => "-->",
+    }
+}
+
+fn to_mermaid(graph: &SkillGraph) -> String {
+    let mut out = String::from("flowchart LR\n");
+    let mut classes: Vec<(String, Vec<String>)> = Vec::new();
+    let mut position: HashMap<&str, usize> = HashMap::new();
+    for (i, node) in graph.nodes.iter().enumerate() {
+        position.insert(node.id.as_str(), i);
+        let id = format!("n{}", i);
+        let label = mermaid_label(&node.label);
+        let shape = match node.node_type.as_str() {
+            "moc" => format!("{{\"{}\"}}", label),
+            "hook" => format!("{{{{\"{}\"}}}}", label),
+            "command" => format!("[/\"{}\"/]", label),
+            "subagent" | "unresolved" | "cycle" => format!("(\"{}\")", label),
+            "script" => format!("[\"{}\"]", label),
+            _ => format!("([\"{}\"])", label),
+        };
+        let _ = writeln!(out, "  {}{}", id, shape);
+
+        let class = mermaid_class(node);
+        match classes.iter_mut().find(|(name, _)| *name == class) {
+            Some((_, members)) => members.push(id),
+            None => classes.push((class, vec![id])),
+        }
+    }
+
+    for edge in &graph.edges {
+        let (Some(source), Some(target)) = (
+            position.get(edge.source.as_str()),
+            position.get(edge.target.as_str()),
+        ) else {
+            continue;
+        };
+        let _ = writeln!(
+            out,
+            "  n{} {}|\"{}\"| n{}",
+            source,
+            mermaid_arrow(graph, &edge.kind),
+            mermaid_label(&edge.kind),
+            target
+        );
+    }
+
+    for (class, members) in &classes {
+        let _ = writeln!(out, "  classDef {} {}", class, mermaid_class_style(class));
+        let _ = writeln!(out, "  class {} {}", members.join(","), class);
+    }
+    out
+}
+
+// ---------------------------------------------------------------------------
+// JSON Graph Format
+// ---------------------------------------------------------------------------
+
+fn to_jgf(graph: &SkillGraph) -> Result<String, String> {
+    let nodes: serde_json::Map<String, serde_json::Value> = graph
+        .nodes
+        .iter()
+        .map(|node| {
+            (
+                node.id.clone(),
+                serde_json::json!({
+                    "label": node.label,
+                    "metadata": {
+                        "node_type": node.node_type,
+                        "category": node.category,
+                        "status": node.status,
+                        "is_moc": node.is_moc,
+                        "is_ghost": node.is_ghost,
+                        "is_broken": node.is_broken,
+                    },
+                }),
+            )
+        })
+        .collect();
+    let edges: Vec<serde_json::Value> = graph
+        .edges
+        .iter()
+        .map(|edge| {
+            serde_json::json!({
+                "source": edge.source,
+                "target": edge.target,
+                "relation": edge.kind,
+                "directed": true,
+                "metadata": { "kind": edge.kind, "spans": edge.spans },
+            })
+        })
+        .collect();
+
+    let document = serde_json::json!({
+        "graph": {
+            "id": "skills",
+            "type": "skill-graph",
+            "directed": true,
+            "nodes": nodes,
+            "edges": edges,
+        }
+    });
+    serde_json::to_string_pretty(&document).map_err(|e| e.to_string())
+}
+
+pub fn export(graph: &SkillGraph, format: ExportFormat) -> Result<String, String> {
+    match format {
+        ExportFormat::Dot => Ok(to_dot(graph)),
+        ExportFormat::Graphml => Ok(to_graphml(graph)),
+        ExportFormat::Mermaid => Ok(to_mermaid(graph)),
+        ExportFormat::Jgf => to_jgf(graph),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub async fn export_graph(
+    workspaces: State<'_, WorkspaceRegistry>,
+    skills: Vec<SkillEntry>,
+    workspace_root: Option<String>,
+    format: ExportFormat,
+    filter: Option<ExportFilter>,
+) -> Result<String, String> {
+    let graph = load_skill_graph(&skills, &workspaces, workspace_root.as_deref())?;
+    match filter {
+        Some(filter) => export(&filter_graph(&graph, &filter)?, format),
+        None => export(&graph, format),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> SkillGraph {
+        let node = |id: &str, node_type: &str, category: Option<&str>, is_ghost: bool| GraphNode {
+            id: id.to_string(),
+            label: format!("{} \"<x>\"", id),
+            node_type: node_type.to_string(),
+            category: category.map(|c| c.to_string()),
+            status: None,
+            is_moc: node_type == "moc",
+            is_ghost,
+            is_broken: false,
+            file: None,
+        };
+        let edge = |source: &str, target: &str, kind: &str| GraphEdge {
+            source: source.to_string(),
+            target: target.to_string(),
+            kind: kind.to_string(),
+            spans: Vec::new(),
+        };
+        let nodes = vec![
+            node("index", "moc", None, false),
+            node("loader", "skill", Some("data"), false),
+            node("gone", "unresolved", None, true),
+        ];
+        let edges = vec![
+            edge("index", "loader", "wiki"),
+            edge("loader", "gone", "related"),
+        ];
+        SkillGraph {
+            node_count: nodes.len(),
+            edge_count: edges.len(),
+            nodes,
+            edges,
+            cycles: Vec::new(),
+            ambiguities: Vec::new(),
+            collisions: Vec::new(),
+            edge_styles: crate::graph_schema::GraphSchema::default().edge_styles(),
+            orphan_scripts: Vec::new(),
+            condensed: None,
+        }
+    }
+
+    #[test]
+    fn test_formats_escape_and_carry_attributes() {
+        let graph = sample();
+
+        let dot = export(&graph, ExportFormat::Dot).unwrap();
+        assert!(dot.contains("\"loader\" [label=\"loader \\\"<x>\\\"\", shape=ellipse"));
+        assert!(dot.contains("category=\"data\""));
+        assert!(dot.contains("\"loader\" -> \"gone\" [kind=\"related\", style=dashed];"));
+
+        let graphml = export(&graph, ExportFormat::Graphml).unwrap();
+        assert!(graphml.contains("<data key=\"label\">index &quot;&lt;x&gt;&quot;</data>"));
+        assert!(graphml.contains("<data key=\"is_ghost\">true</data>"));
+
+        let mermaid = export(&graph, ExportFormat::Mermaid).unwrap();
+        assert!(mermaid.starts_with("flowchart LR\n"));
+        assert!(mermaid.contains("  n0{\"index #quot;<x>#quot;\"}\n"));
+        assert!(mermaid.contains("  n1 -.->|\"related\"| n2\n"));
+        assert!(mermaid.contains("  classDef ghost "));
+        assert!(mermaid.contains("  class n2 ghost\n"));
+        for class in ["moc", "skill"] {
+            assert!(mermaid.contains(&format!("  classDef {} ", class)));
+        }
+
+        // Arrows follow the schema's styles, as in DOT
+        let mut styled = sample();
+        styled
+            .edge_styles
+            .insert("wiki".to_string(), "bold".to_string());
+        styled.edges[0].kind = "wiki".to_string();
+        styled.edges[1].kind = "see \"a|b\" #1".to_string();
+        styled.nodes[2].is_ghost = false;
+        styled.nodes[2].is_broken = true;
+        let mermaid = export(&styled, ExportFormat::Mermaid).unwrap();
+        assert!(mermaid.contains("  n0 ==>|\"wiki\"| n1\n"));
+        assert!(mermaid.contains("  n1 -->|\"see #quot;a|b#quot; #35;1\"| n2\n"));
+        assert!(mermaid.contains("  classDef broken "));
+        assert!(mermaid.contains("  class n2 broken\n"));
+
+        let jgf: serde_json::Value =
+            serde_json::from_str(&export(&graph, ExportFormat::Jgf).unwrap()).unwrap();
+        assert_eq!(jgf["graph"]["nodes"]["index"]["metadata"]["is_moc"], true);
+        assert_eq!(jgf["graph"]["edges"][0]["relation"], "wiki");
+    }
+
+    #[test]
+    fn test_filter_selects_subgraph() {
+        let graph = sample();
+        let filter = ExportFilter {
+            exclude_ghosts: true,
+            ..Default::default()
+        };
+        let filtered = filter_graph(&graph, &filter).unwrap();
+        assert_eq!(filtered.node_count, 2);
+        assert_eq!(filtered.edge_count, 1);
+
+        let filter = ExportFilter {
+            root: Some("Loader".to_string()),
+            ..Default::default()
+        };
+        let filtered = filter_graph(&graph, &filter).unwrap();
+        let ids: Vec<&str> = filtered.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, vec!["loader", "gone"]);
+        assert!(filter_graph(
+            &graph,
+            &ExportFilter {
+                root: Some("missing".to_string()),
+                ..Default::default()
+            }
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,383 @@
+//! graph_metrics.rs — Structural metrics over the skill graph.
+//!
+//! Metrics cover skill and MOC nodes and the edges between them; ghosts,
+//! file nodes such as scripts and self-links are left out, and links of
+//! several kinds between the same two skills count once, so a skill's score
+//! reflects only which other skills it is connected to.
+//!
+//!   - degree: distinct skills linking in and out
+//!   - PageRank: damping 0.85; dangling skills spread their rank evenly
+//!   - betweenness: Brandes' algorithm, normalized by `(n-1)(n-2)`
+//!   - orphans: non-MOC skills nothing links to that belong to no MOC
+//!   - sources link out but are not linked to; sinks the reverse
+//!   - per category: internal and cross-category edges, density and the
+//!     number of weakly connected pieces the category falls into
+//!
+//! Tauri commands exposed:
+//!   - `graph_metrics` → `GraphMetrics` for the skills passed in
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use tauri::State;
+
+use crate::fs_scan::SkillEntry;
+use crate::graph_builder::{load_skill_graph, GraphNode, SkillGraph};
+use crate::workspace::WorkspaceRegistry;
+
+const PAGERANK_DAMPING: f64 = 0.85;
+const PAGERANK_MAX_ITERATIONS: usize = 100;
+const PAGERANK_TOLERANCE: f64 = 1e-10;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeMetrics {
+    pub id: String,
+    pub label: String,
+    pub category: Option<String>,
+    pub is_moc: bool,
+    pub in_degree: usize,
+    pub out_degree: usize,
+    pub pagerank: f64,
+    pub betweenness: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryConnectivity {
+    /// `None` for skills without a `category`.
+    pub category: Option<String>,
+    pub skill_count: usize,
+    pub internal_edges: usize,
+    pub outgoing_edges: usize,
+    pub incoming_edges: usize,
+    /// Internal edges over the `n(n-1)` possible; 0 below two skills.
+    pub density: f64,
+    pub components: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphMetrics {
+    /// Highest PageRank first, ties by id.
+    pub nodes: Vec<NodeMetrics>,
+    /// Non-MOC skills with no incoming edges and no MOC membership, sorted.
+    /// A skill is a member of a MOC when either links to the other: the MOC
+    /// lists the skill, or the skill declares the MOC.
+    pub orphans: Vec<String>,
+    pub sources: Vec<String>,
+    pub sinks: Vec<String>,
+    pub categories: Vec<CategoryConnectivity>,
+}
+
+fn is_skill_node(node: &GraphNode) -> bool {
+    !node.is_ghost && node.file.is_none() && node.node_type != "cycle"
+}
+
+fn pagerank(outgoing: &[Vec<usize>]) -> Vec<f64> {
+    let n = outgoing.len();
+    if n == 0 {
+        return Vec::new();
+    }
+    let base = (1.0 - PAGERANK_DAMPING) / n as f64;
+    let mut rank = vec![1.0 / n as f64; n];
+
+    for _ in 0..PAGERANK_MAX_ITERATIONS {
+        let dangling: f64 = (0..n)
+            .filter(|&v| outgoing[v].is_empty())
+            .map(|v| rank[v])
+            .sum();
+        let mut next = vec![base + PAGERANK_DAMPING * dangling / n as f64; n];
+        for (v, targets) in outgoing.iter().enumerate() {
+            let share = PAGERANK_DAMPING * rank[v] / targets.len().max(1) as f64;
+            for &w in targets {
+                next[w] += share;
+            }
+        }
+        let delta: f64 = next.iter().zip(&rank).map(|(a, b)| (a - b).abs()).sum();
+        rank = next;
+        if delta < PAGERANK_TOLERANCE {
+            break;
+        }
+    }
+    rank
+}
+
+/// Brandes' betweenness for an unweighted directed graph, normalized.
+fn betweenness(outgoing: &[Vec<usize>]) -> Vec<f64> {
+    let n = outgoing.len();
+    let mut centrality = vec![0.0; n];
+
+    for s in 0..n {
+        let mut order: Vec<usize> = Vec::new();
+        let mut predecessors: Vec<Vec<usize>> = vec![Vec::new(); n];
+        let mut paths = vec![0.0f64; n];
+        let mut distance: Vec<Option<usize>> = vec![None; n];
+        paths[s] = 1.0;
+        distance[s] = Some(0);
+        let mut queue = VecDeque::from([s]);
+
+        while let Some(v) = queue.pop_front() {
+            order.push(v);
+            let next = distance[v].unwrap_or(0) + 1;
+            for &w in &outgoing[v] {
+                if distance[w].is_none() {
+                    distance[w] = Some(next);
+                    queue.push_back(w);
+                }
+                if distance[w] == Some(next) {
+                    paths[w] += paths[v];
+                    predecessors[w].push(v);
+                }
+            }
+        }
+
+        let mut dependency = vec![0.0f64; n];
+        for &w in order.iter().rev() {
+            for &v in &predecessors[w] {
+                dependency[v] += paths[v] / paths[w] * (1.0 + dependency[w]);
+            }
+            if w != s {
+                centrality[w] += dependency[w];
+            }
+        }
+    }
+
+    if n > 2 {
+        let scale = ((n - 1) * (n - 2)) as f64;
+        for value in &mut centrality {
+            *value /= scale;
+        }
+    }
+    centrality
+}
+
+/// Number of weakly connected pieces among `members`, using only edges
+/// between them.
+fn count_components(members: &[usize], outgoing: &[Vec<usize>], incoming: &[Vec<usize>]) -> usize {
+    // Member → visited yet
+    let mut seen: HashMap<usize, bool> = members.iter().map(|&m| (m, false)).collect();
+    let mut components = 0;
+    for &start in members {
+        if seen[&start] {
+            continue;
+        }
+        components += 1;
+        seen.insert(start, true);
+        let mut stack = vec![start];
+        while let Some(v) = stack.pop() {
+            for &w in outgoing[v].iter().chain(&incoming[v]) {
+                if seen.get(&w) == Some(&false) {
+                    seen.insert(w, true);
+                    stack.push(w);
+                }
+            }
+        }
+    }
+    components
+}
+
+pub fn compute_metrics(graph: &SkillGraph) -> GraphMetrics {
+    let skills: Vec<&GraphNode> = graph.nodes.iter().filter(|n| is_skill_node(n)).collect();
+    let position: HashMap<&str, usize> = skills
+        .iter()
+        .enumerate()
+        .map(|(i, node)| (node.id.as_str(), i))
+        .collect();
+
+    let n = skills.len();
+    let mut outgoing: Vec<Vec<usize>> = vec![Vec::new(); n];
+    let mut incoming: Vec<Vec<usize>> = vec![Vec::new(); n];
+    for edge in &graph.edges {
+        let (Some(&source), Some(&target)) = (
+            position.get(edge.source.as_str()),
+            position.get(edge.target.as_str()),
+        ) else {
+            continue;
+        };
+        if source != target && !outgoing[source].contains(&target) {
+            outgoing[source].push(target);
+            incoming[target].push(source);
+        }
+    }
+
+    let ranks = pagerank(&outgoing);
+    let centrality = betweenness(&outgoing);
+
+    let mut orphans = Vec::new();
+    let mut sources = Vec::new();
+    let mut sinks = Vec::new();
+    for (i, node) in skills.iter().enumerate() {
+        let (in_degree, out_degree) = (incoming[i].len(), outgoing[i].len());
+        let in_moc = outgoing[i]
+            .iter()
+            .chain(&incoming[i])
+            .any(|&w| skills[w].is_moc);
+        if in_degree == 0 && !node.is_moc && !in_moc {
+            orphans.push(node.id.clone());
+        }
+        if in_degree == 0 && out_degree > 0 {
+            sources.push(node.id.clone());
+        }
+        if out_degree == 0 && in_degree > 0 {
+            sinks.push(node.id.clone());
+        }
+    }
+    orphans.sort();
+    sources.sort();
+    sinks.sort();
+
+    let mut by_category: BTreeMap<Option<&str>, Vec<usize>> = BTreeMap::new();
+    for (i, node) in skills.iter().enumerate() {
+        by_category
+            .entry(node.category.as_deref())
+            .or_default()
+            .push(i);
+    }
+    let categories = by_category
+        .into_iter()
+        .map(|(category, members)| {
+            let same = |w: usize| skills[w].category.as_deref() == category;
+            let internal_edges: usize = members
+                .iter()
+                .map(|&v| outgoing[v].iter().filter(|&&w| same(w)).count())
+                .sum();
+            let outgoing_edges: usize = members
+                .iter()
+                .map(|&v| outgoing[v].iter().filter(|&&w| !same(w)).count())
+                .sum();
+            let incoming_edges: usize = members
+                .iter()
+                .map(|&v| incoming[v].iter().filter(|&&w| !same(w)).count())
+                .sum();
+            let size = members.len();
+            let density = if size > 1 {
+                internal_edges as f64 / (size * (size - 1)) as f64
+            } else {
+                0.0
+            };
+
+            // Components only follow edges inside the category
+            let local = |edges: &[Vec<usize>]| -> Vec<Vec<usize>> {
+                edges
+                    .iter()
+                    .map(|targets| targets.iter().copied().filter(|&w| same(w)).collect())
+                    .collect()
+            };
+            CategoryConnectivity {
+                category: category.map(|c| c.to_string()),
+                skill_count: size,
+                internal_edges,
+                outgoing_edges,
+                incoming_edges,
+                density,
+                components: count_components(&members, &local(&outgoing), &local(&incoming)),
+            }
+        })
+        .collect();
+
+    let mut nodes: Vec<NodeMetrics> = skills
+        .iter()
+        .enumerate()
+        .map(|(i, node)| NodeMetrics {
+            id: node.id.clone(),
+            label: node.label.clone(),
+            category: node.category.clone(),
+            is_moc: node.is_moc,
+            in_degree: incoming[i].len(),
+            out_degree: outgoing[i].len(),
+            pagerank: ranks[i],
+            betweenness: centrality[i],
+        })
+        .collect();
+    nodes.sort_by(|a, b| {
+        b.pagerank
+            .total_cmp(&a.pagerank)
+            .then_with(|| a.id.cmp(&b.id))
+    });
+
+    GraphMetrics {
+        nodes,
+        orphans,
+        sources,
+        sinks,
+        categories,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub async fn graph_metrics(
+    workspaces: State<'_, WorkspaceRegistry>,
+    skills: Vec<SkillEntry>,
+    workspace_root: Option<String>,
+) -> Result<GraphMetrics, String> {
+    let graph = load_skill_graph(&skills, &workspaces, workspace_root.as_deref())?;
+    Ok(compute_metrics(&graph))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph_builder::build_skill_graph;
+    use crate::test_support::skill;
+
+    #[test]
+    fn test_metrics_over_small_library() {
+        // index (moc) → loader → cleaner → pandas; cleaner also → ghost;
+        // lonely links nowhere; tagged links only to the moc
+        let graph = build_skill_graph(&[
+            skill(
+                "index",
+                serde_json::json!({"moc": true, "related": ["loader"]}),
+            ),
+            skill(
+                "loader",
+                serde_json::json!({"category": "data", "related": ["cleaner"], "scripts": ["run.sh"]}),
+            ),
+            skill(
+                "cleaner",
+                serde_json::json!({"category": "data", "related": ["pandas", "missing"]}),
+            ),
+            skill("pandas", serde_json::json!({"category": "python"})),
+            skill("lonely", serde_json::json!({"category": "data"})),
+            skill("tagged", serde_json::json!({"related": ["index"]})),
+        ]);
+        let metrics = compute_metrics(&graph);
+
+        assert_eq!(metrics.nodes.len(), 6);
+        let node = |id: &str| metrics.nodes.iter().find(|n| n.id == id).unwrap();
+        assert_eq!(
+            (node("cleaner").in_degree, node("cleaner").out_degree),
+            (1, 1)
+        );
+        assert_eq!(
+            (node("loader").in_degree, node("loader").out_degree),
+            (1, 1)
+        );
+        // tagged → index → loader → cleaner → pandas: loader is between
+        // 4 of the 20 ordered pairs, cleaner between 3
+        assert!((node("loader").betweenness - 4.0 / 20.0).abs() < 1e-9);
+        assert!((node("cleaner").betweenness - 3.0 / 20.0).abs() < 1e-9);
+        assert_eq!(node("pandas").betweenness, 0.0);
+
+        let total: f64 = metrics.nodes.iter().map(|n| n.pagerank).sum();
+        assert!((total - 1.0).abs() < 1e-6);
+        assert_eq!(metrics.nodes[0].id, "pandas");
+
+        assert_eq!(metrics.orphans, vec!["lonely"]);
+        assert_eq!(metrics.sources, vec!["tagged"]);
+        assert_eq!(metrics.sinks, vec!["pandas"]);
+
+        let data = metrics
+            .categories
+            .iter()
+            .find(|c| c.category.as_deref() == Some("data"))
+            .unwrap();
+        assert_eq!(data.skill_count, 3);
+        assert_eq!(data.internal_edges, 1);
+        assert_eq!(data.outgoing_edges, 1);
+        assert_eq!(data.incoming_edges, 1);
+        assert_eq!(data.components, 2);
+        assert!((data.density - 1.0 / 6.0).abs() < 1e-9);
+    }
+}
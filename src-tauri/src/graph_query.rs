@@ -0,0 +1,606 @@
+//! graph_query.rs — Traversal queries over the skill graph.
+//!
+//! An edge `a → b` means `a` links to `b`, so `b` is one of `a`'s
+//! dependencies and `a` is one of `b`'s dependents. Node ids are accepted in
+//! any spelling `normalize_id` folds together; `script:` ids are used as-is.
+//!
+//! Tauri commands exposed:
+//!   - `graph_dependencies` → nodes reachable from a skill, with depth
+//!   - `graph_dependents`   → nodes that reach a skill, with depth
+//!   - `graph_shortest_path` → fewest-hop path between two nodes
+//!   - `graph_all_paths`    → simple paths up to a length limit
+//!   - `graph_impact`       → skills and MOCs reaching a node, grouped by
+//!     category and status
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use tauri::State;
+
+use crate::fs_scan::SkillEntry;
+use crate::graph_builder::{load_skill_graph, normalize_id, GraphEdge, GraphNode, SkillGraph};
+use crate::workspace::WorkspaceRegistry;
+
+/// Default `max_length` for `graph_all_paths`, in edges.
+const DEFAULT_MAX_PATH_LENGTH: usize = 6;
+/// Default cap on the number of paths `graph_all_paths` returns.
+const DEFAULT_PATH_LIMIT: usize = 100;
+/// Longest `max_length` `graph_all_paths` honours, in edges.
+const MAX_PATH_LENGTH: usize = 12;
+/// Edges `all_paths` tries before giving up; the number of simple paths
+/// grows exponentially in a densely linked library.
+const PATH_SEARCH_BUDGET: usize = 1_000_000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReachedNode {
+    #[serde(flatten)]
+    pub node: GraphNode,
+    /// Hops from the queried node along the shortest route.
+    pub depth: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphPath {
+    /// Node ids from start to end.
+    pub nodes: Vec<String>,
+    /// The edge taken at each hop; when several kinds join the same pair,
+    /// the first one in the graph.
+    pub edges: Vec<GraphEdge>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImpactGroup {
+    pub category: Option<String>,
+    pub status: Option<String>,
+    pub nodes: Vec<ReachedNode>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImpactReport {
+    pub target: GraphNode,
+    pub groups: Vec<ImpactGroup>,
+    pub moc_count: usize,
+    pub skill_count: usize,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Direction {
+    Outgoing,
+    Incoming,
+}
+
+// ---------------------------------------------------------------------------
+// Indexed graph
+// ---------------------------------------------------------------------------
+
+/// Adjacency lists over a `SkillGraph`, by node position. Each entry is
+/// `(neighbour, edge index)`, with one entry per distinct neighbour.
+pub(crate) struct GraphIndex<'a> {
+    pub graph: &'a SkillGraph,
+    position: HashMap<&'a str, usize>,
+    outgoing: Vec<Vec<(usize, usize)>>,
+    incoming: Vec<Vec<(usize, usize)>>,
+}
+
+impl<'a> GraphIndex<'a> {
+    pub fn new(graph: &'a SkillGraph) -> Self {
+        let position: HashMap<&str, usize> = graph
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(i, node)| (node.id.as_str(), i))
+            .collect();
+        let mut outgoing: Vec<Vec<(usize, usize)>> = vec![Vec::new(); graph.nodes.len()];
+        let mut incoming: Vec<Vec<(usize, usize)>> = vec![Vec::new(); graph.nodes.len()];
+
+        for (edge_index, edge) in graph.edges.iter().enumerate() {
+            let (Some(&source), Some(&target)) = (
+                position.get(edge.source.as_str()),
+                position.get(edge.target.as_str()),
+            ) else {
+                continue;
+            };
+            if !outgoing[source].iter().any(|&(n, _)| n == target) {
+                outgoing[source].push((target, edge_index));
+                incoming[target].push((source, edge_index));
+            }
+        }
+
+        GraphIndex {
+            graph,
+            position,
+            outgoing,
+            incoming,
+        }
+    }
+
+    /// Position of a node by id, trying the id verbatim before normalizing.
+    pub fn find(&self, id: &str) -> Result<usize, String> {
+        self.position
+            .get(id)
+            .or_else(|| self.position.get(normalize_id(id).as_str()))
+            .copied()
+            .ok_or_else(|| format!("Unknown skill: {}", id))
+    }
+
+    pub fn node(&self, position: usize) -> &'a GraphNode {
+        &self.graph.nodes[position]
+    }
+
+    pub fn outgoing(&self, position: usize) -> &[(usize, usize)] {
+        &self.outgoing[position]
+    }
+
+    pub fn incoming(&self, position: usize) -> &[(usize, usize)] {
+        &self.incoming[position]
+    }
+
+    fn neighbours(&self, position: usize, direction: Direction) -> &[(usize, usize)] {
+        match direction {
+            Direction::Outgoing => self.outgoing(position),
+            Direction::Incoming => self.incoming(position),
+        }
+    }
+
+    /// Breadth-first distances from `start`, excluding `start` itself.
+    fn reach(
+        &self,
+        start: usize,
+        direction: Direction,
+        max_depth: Option<usize>,
+    ) -> Vec<(usize, usize)> {
+        let mut depth: Vec<Option<usize>> = vec![None; self.graph.nodes.len()];
+        depth[start] = Some(0);
+        let mut queue = VecDeque::from([start]);
+        let mut reached = Vec::new();
+
+        while let Some(current) = queue.pop_front() {
+            let next_depth = depth[current].unwrap_or(0) + 1;
+            if max_depth.is_some_and(|max| next_depth > max) {
+                continue;
+            }
+            for &(neighbour, _) in self.neighbours(current, direction) {
+                if depth[neighbour].is_none() {
+                    depth[neighbour] = Some(next_depth);
+                    reached.push((neighbour, next_depth));
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+
+        reached
+    }
+
+    fn reached_nodes(
+        &self,
+        id: &str,
+        direction: Direction,
+        max_depth: Option<usize>,
+    ) -> Result<Vec<ReachedNode>, String> {
+        let start = self.find(id)?;
+        Ok(self
+            .reach(start, direction, max_depth)
+            .into_iter()
+            .map(|(position, depth)| ReachedNode {
+                node: self.node(position).clone(),
+                depth,
+            })
+            .collect())
+    }
+
+    fn to_path(&self, positions: &[usize], edge_indices: &[usize]) -> GraphPath {
+        GraphPath {
+            nodes: positions.iter().map(|&p| self.node(p).id.clone()).collect(),
+            edges: edge_indices
+                .iter()
+                .map(|&e| self.graph.edges[e].clone())
+                .collect(),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Queries
+// ---------------------------------------------------------------------------
+
+pub fn dependencies(
+    graph: &SkillGraph,
+    id: &str,
+    max_depth: Option<usize>,
+) -> Result<Vec<ReachedNode>, String> {
+    GraphIndex::new(graph).reached_nodes(id, Direction::Outgoing, max_depth)
+}
+
+pub fn dependents(
+    graph: &SkillGraph,
+    id: &str,
+    max_depth: Option<usize>,
+) -> Result<Vec<ReachedNode>, String> {
+    GraphIndex::new(graph).reached_nodes(id, Direction::Incoming, max_depth)
+}
+
+pub fn shortest_path(
+    graph: &SkillGraph,
+    from: &str,
+    to: &str,
+) -> Result<Option<GraphPath>, String> {
+    let index = GraphIndex::new(graph);
+    let start = index.find(from)?;
+    let end = index.find(to)?;
+
+    // Predecessor (node, edge) for each visited node
+    let mut came_from: Vec<Option<(usize, usize)>> = vec![None; graph.nodes.len()];
+    let mut visited = vec![false; graph.nodes.len()];
+    visited[start] = true;
+    let mut queue = VecDeque::from([start]);
+
+    while let Some(current) = queue.pop_front() {
+        if current == end {
+            break;
+        }
+        for &(neighbour, edge) in index.outgoing(current) {
+            if !visited[neighbour] {
+                visited[neighbour] = true;
+                came_from[neighbour] = Some((current, edge));
+                queue.push_back(neighbour);
+            }
+        }
+    }
+
+    if !visited[end] {
+        return Ok(None);
+    }
+
+    let mut positions = vec![end];
+    let mut edge_indices = Vec::new();
+    let mut current = end;
+    while let Some((previous, edge)) = came_from[current] {
+        positions.push(previous);
+        edge_indices.push(edge);
+        current = previous;
+    }
+    positions.reverse();
+    edge_indices.reverse();
+    Ok(Some(index.to_path(&positions, &edge_indices)))
+}
+
+/// Simple paths from `from` to `to` of at most `max_length` edges (capped
+/// at `MAX_PATH_LENGTH`), stopping after `limit` paths or once the search
+/// budget is spent, whichever comes first.
+pub fn all_paths(
+    graph: &SkillGraph,
+    from: &str,
+    to: &str,
+    max_length: usize,
+    limit: usize,
+) -> Result<Vec<GraphPath>, String> {
+    let index = GraphIndex::new(graph);
+    let start = index.find(from)?;
+    let end = index.find(to)?;
+    let max_length = max_length.min(MAX_PATH_LENGTH);
+    let mut budget = PATH_SEARCH_BUDGET;
+    let mut paths = Vec::new();
+    if start == end {
+        paths.push(index.to_path(&[start], &[]));
+        return Ok(paths);
+    }
+
+    // Depth-first over simple paths; `cursor[i]` is the next outgoing entry
+    // to try from `positions[i]`.
+    let mut positions = vec![start];
+    let mut edge_indices: Vec<usize> = Vec::new();
+    let mut cursor = vec![0usize];
+    let mut on_path = vec![false; graph.nodes.len()];
+    on_path[start] = true;
+
+    while let Some(&current) = positions.last() {
+        if paths.len() >= limit || budget == 0 {
+            break;
+        }
+        budget -= 1;
+        let depth = positions.len() - 1;
+        let next = index.outgoing(current).get(cursor[depth]).copied();
+        cursor[depth] += 1;
+
+        match next {
+            Some((neighbour, edge)) if !on_path[neighbour] && depth < max_length => {
+                if neighbour == end {
+                    positions.push(neighbour);
+                    edge_indices.push(edge);
+                    paths.push(index.to_path(&positions, &edge_indices));
+                    positions.pop();
+                    edge_indices.pop();
+                } else {
+                    on_path[neighbour] = true;
+                    positions.push(neighbour);
+                    edge_indices.push(edge);
+                    cursor.push(0);
+                }
+            }
+            Some(_) => {}
+            None => {
+                on_path[current] = false;
+                positions.pop();
+                edge_indices.pop();
+                cursor.pop();
+            }
+        }
+    }
+
+    Ok(paths)
+}
+
+pub fn impact(
+    graph: &SkillGraph,
+    id: &str,
+    max_depth: Option<usize>,
+) -> Result<ImpactReport, String> {
+    let index = GraphIndex::new(graph);
+    let target = index.node(index.find(id)?).clone();
+
+    let mut groups: BTreeMap<(Option<String>, Option<String>), Vec<ReachedNode>> = BTreeMap::new();
+    let mut moc_count = 0;
+    let mut skill_count = 0;
+    for reached in index.reached_nodes(&target.id, Direction::Incoming, max_depth)? {
+        if reached.node.is_ghost || reached.node.node_type == "script" {
+            continue;
+        }
+        if reached.node.is_moc {
+            moc_count += 1;
+        } else {
+            skill_count += 1;
+        }
+        groups
+            .entry((reached.node.category.clone(), reached.node.status.clone()))
+            .or_default()
+            .push(reached);
+    }
+
+    Ok(ImpactReport {
+        target,
+        groups: groups
+            .into_iter()
+            .map(|((category, status), mut nodes)| {
+                nodes.sort_by(|a, b| {
+                    a.depth
+                        .cmp(&b.depth)
+                        .then_with(|| a.node.id.cmp(&b.node.id))
+                });
+                ImpactGroup {
+                    category,
+                    status,
+                    nodes,
+                }
+            })
+            .collect(),
+        moc_count,
+        skill_count,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub async fn graph_dependencies(
+    workspaces: State<'_, WorkspaceRegistry>,
+    skills: Vec<SkillEntry>,
+    workspace_root: Option<String>,
+    node_id: String,
+    max_depth: Option<usize>,
+) -> Result<Vec<ReachedNode>, String> {
+    dependencies(
+        &load_skill_graph(&skills, &workspaces, workspace_root.as_deref())?,
+        &node_id,
+        max_depth,
+    )
+}
+
+#[tauri::command]
+pub async fn graph_dependents(
+    workspaces: State<'_, WorkspaceRegistry>,
+    skills: Vec<SkillEntry>,
+    workspace_root: Option<String>,
+    node_id: String,
+    max_depth: Option<usize>,
+) -> Result<Vec<ReachedNode>, String> {
+    dependents(
+        &load_skill_graph(&skills, &workspaces, workspace_root.as_deref())?,
+        &node_id,
+        max_depth,
+    )
+}
+
+#[tauri::command]
+pub async fn graph_shortest_path(
+    workspaces: State<'_, WorkspaceRegistry>,
+    skills: Vec<SkillEntry>,
+    workspace_root: Option<String>,
+    from: String,
+    to: String,
+) -> Result<Option<GraphPath>, String> {
+    shortest_path(
+        &load_skill_graph(&skills, &workspaces, workspace_root.as_deref())?,
+        &from,
+        &to,
+    )
+}
+
+#[tauri::command]
+pub async fn graph_all_paths(
+    workspaces: State<'_, WorkspaceRegistry>,
+    skills: Vec<SkillEntry>,
+    workspace_root: Option<String>,
+    from: String,
+    to: String,
+    max_length: Option<usize>,
+    limit: Option<usize>,
+) -> Result<Vec<GraphPath>, String> {
+    all_paths(
+        &load_skill_graph(&skills, &workspaces, workspace_root.as_deref())?,
+        &from,
+        &to,
+        max_length.unwrap_or(DEFAULT_MAX_PATH_LENGTH),
+        limit.unwrap_or(DEFAULT_PATH_LIMIT),
+    )
+}
+
+#[tauri::command]
+pub async fn graph_impact(
+    workspaces: State<'_, WorkspaceRegistry>,
+    skills: Vec<SkillEntry>,
+    workspace_root: Option<String>,
+    node_id: String,
+    max_depth: Option<usize>,
+) -> Result<ImpactReport, String> {
+    impact(
+        &load_skill_graph(&skills, &workspaces, workspace_root.as_deref())?,
+        &node_id,
+        max_depth,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(id: &str, category: Option<&str>, is_moc: bool) -> GraphNode {
+        GraphNode {
+            id: id.to_string(),
+            label: id.to_string(),
+            node_type: if is_moc { "moc" } else { "skill" }.to_string(),
+            category: category.map(|c| c.to_string()),
+            status: None,
+            is_moc,
+            is_ghost: false,
+            is_broken: false,
+            file: None,
+        }
+    }
+
+    fn edge(source: &str, target: &str) -> GraphEdge {
+        GraphEdge {
+            source: source.to_string(),
+            target: target.to_string(),
+            kind: "related".to_string(),
+            spans: Vec::new(),
+        }
+    }
+
+    /// index → data-moc → {loader, cleaner}; loader → cleaner → pandas;
+    /// loader → pandas
+    fn sample() -> SkillGraph {
+        let nodes = vec![
+            node("index", None, true),
+            node("data-moc", Some("data"), true),
+            node("loader", Some("data"), false),
+            node("cleaner", Some("data"), false),
+            node("pandas", Some("python"), false),
+        ];
+        let edges = vec![
+            edge("index", "data-moc"),
+            edge("data-moc", "loader"),
+            edge("data-moc", "cleaner"),
+            edge("loader", "cleaner"),
+            edge("cleaner", "pandas"),
+            edge("loader", "pandas"),
+        ];
+        SkillGraph {
+            node_count: nodes.len(),
+            edge_count: edges.len(),
+            nodes,
+            edges,
+            cycles: Vec::new(),
+            ambiguities: Vec::new(),
+            collisions: Vec::new(),
+            edge_styles: Default::default(),
+            orphan_scripts: Vec::new(),
+            condensed: None,
+        }
+    }
+
+    #[test]
+    fn test_reach_and_paths() {
+        let graph = sample();
+
+        let deps: Vec<(String, usize)> = dependencies(&graph, "Data MOC", Some(1))
+            .unwrap()
+            .into_iter()
+            .map(|r| (r.node.id, r.depth))
+            .collect();
+        assert_eq!(
+            deps,
+            vec![("loader".to_string(), 1), ("cleaner".to_string(), 1)]
+        );
+        assert_eq!(dependents(&graph, "pandas", None).unwrap().len(), 4);
+
+        let shortest = shortest_path(&graph, "index", "pandas").unwrap().unwrap();
+        assert_eq!(
+            shortest.nodes,
+            vec!["index", "data-moc", "loader", "pandas"]
+        );
+        assert_eq!(shortest.edges.len(), 3);
+        assert!(shortest_path(&graph, "pandas", "index").unwrap().is_none());
+
+        assert_eq!(
+            all_paths(&graph, "data-moc", "pandas", 6, 100)
+                .unwrap()
+                .len(),
+            3
+        );
+        assert_eq!(
+            all_paths(&graph, "data-moc", "pandas", 2, 100)
+                .unwrap()
+                .len(),
+            2
+        );
+        assert!(dependencies(&graph, "nope", None).is_err());
+    }
+
+    #[test]
+    fn test_all_paths_stops_in_a_dense_graph() {
+        // Every node links to every other, and nothing reaches the target
+        let names: Vec<String> = (0..14).map(|i| format!("n{}", i)).collect();
+        let mut graph = sample();
+        graph.nodes = names.iter().map(|n| node(n, None, false)).collect();
+        graph.nodes.push(node("target", None, false));
+        graph.edges = names
+            .iter()
+            .flat_map(|a| {
+                names
+                    .iter()
+                    .filter(move |b| *b != a)
+                    .map(move |b| edge(a, b))
+            })
+            .collect();
+
+        let paths = all_paths(&graph, "n0", "target", usize::MAX, usize::MAX).unwrap();
+        assert!(paths.is_empty());
+    }
+
+    #[test]
+    fn test_impact_groups_by_category_and_status() {
+        let report = impact(&sample(), "pandas", None).unwrap();
+        assert_eq!(report.target.id, "pandas");
+        assert_eq!(report.moc_count, 2);
+        assert_eq!(report.skill_count, 2);
+
+        let groups: Vec<(Option<&str>, Vec<&str>)> = report
+            .groups
+            .iter()
+            .map(|g| {
+                (
+                    g.category.as_deref(),
+                    g.nodes.iter().map(|r| r.node.id.as_str()).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            groups,
+            vec![
+                (None, vec!["index"]),
+                (Some("data"), vec!["cleaner", "loader", "data-moc"]),
+            ]
+        );
+    }
+}
@@ -0,0 +1,272 @@
+//! graph_resolver.rs — Resolve link targets to graph node ids.
+//!
+//! A target is looked up against each skill's name, then its `aliases:` and
+//! `slug`, then its file stem (the directory for a bundle `SKILL.md`).
+//! Targets containing a `/` are matched as paths instead, against the end
+//! of the skill's path with or without `.md`; those starting with `./` or
+//! `../` are taken relative to the linking skill's directory and must match
+//! a skill's path exactly. Names and stems compare as `normalize_id` does;
+//! paths compare case-insensitively.
+//!
+//! Node ids come from `graph_node_ids`, so skills whose names collide are
+//! told apart by their `--N` ids; their shared name is ambiguous.
+//!
+//! The first tier with any match wins. When that tier matches more than one
+//! skill the target is ambiguous and no node is picked.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+use crate::fs_scan::{fallback_skill_name, SkillEntry};
+use crate::graph_builder::normalize_id;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchKind {
+    Name,
+    Alias,
+    Stem,
+    Path,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Resolution {
+    Found {
+        id: String,
+        matched_by: MatchKind,
+    },
+    Ambiguous {
+        candidates: Vec<String>,
+        matched_by: MatchKind,
+    },
+    Missing,
+}
+
+#[derive(Default)]
+pub struct SkillResolver {
+    names: HashMap<String, Vec<String>>,
+    aliases: HashMap<String, Vec<String>>,
+    stems: HashMap<String, Vec<String>>,
+    /// Lowercased path with `/` separators and no `.md`, and its node id.
+    paths: Vec<(String, String)>,
+}
+
+fn add(table: &mut HashMap<String, Vec<String>>, key: &str, id: &str) {
+    let key = normalize_id(key);
+    if key.is_empty() {
+        return;
+    }
+    let ids = table.entry(key).or_default();
+    if !ids.iter().any(|existing| existing == id) {
+        ids.push(id.to_string());
+    }
+}
+
+fn normalize_path(path: &str) -> String {
+    let path = path.trim().replace('\\', "/").to_lowercase();
+    let path = path.trim_start_matches("./").trim_end_matches('/');
+    path.strip_suffix(".md").unwrap_or(path).to_string()
+}
+
+impl SkillResolver {
+    /// `ids` is each skill's node id, as from `graph_node_ids`.
+    pub fn new(skills: &[SkillEntry], ids: &[Option<String>]) -> Self {
+        let mut resolver = SkillResolver::default();
+        for (skill, id) in skills.iter().zip(ids) {
+            let (Some(fm), Some(id)) = (&skill.frontmatter, id) else {
+                continue;
+            };
+
+            add(&mut resolver.names, &skill.name, id);
+            let aliases = fm
+                .get("aliases")
+                .and_then(|v| v.as_array())
+                .into_iter()
+                .flatten()
+                .chain(fm.get("slug"))
+                .filter_map(|v| v.as_str());
+            for alias in aliases {
+                add(&mut resolver.aliases, alias, id);
+            }
+            add(
+                &mut resolver.stems,
+                fallback_skill_name(Path::new(&skill.path)),
+                id,
+            );
+
+            let path = normalize_path(&skill.path);
+            // A bundle is also reachable by its directory
+            if let Some(dir) = path.strip_suffix("/skill") {
+                resolver.paths.push((dir.to_string(), id.clone()));
+            }
+            resolver.paths.push((path, id.clone()));
+        }
+        resolver
+    }
+
+    /// Resolve `target` as written in the skill at `source_path`.
+    pub fn resolve_from(&self, target: &str, source_path: &str) -> Resolution {
+        let target = target.trim();
+        if !(target.starts_with("./") || target.starts_with("../")) {
+            return self.resolve(target);
+        }
+
+        let mut joined = PathBuf::new();
+        let base = Path::new(source_path).parent().unwrap_or(Path::new(""));
+        for component in base.join(target).components() {
+            match component {
+                Component::CurDir => {}
+                Component::ParentDir => {
+                    joined.pop();
+                }
+                other => joined.push(other),
+            }
+        }
+        let wanted = normalize_path(&joined.to_string_lossy());
+        let ids: Vec<String> = self
+            .paths
+            .iter()
+            .filter(|(path, _)| *path == wanted)
+            .map(|(_, id)| id.clone())
+            .collect();
+        if ids.is_empty() {
+            Resolution::Missing
+        } else {
+            Self::pick(ids, MatchKind::Path)
+        }
+    }
+
+    pub fn resolve(&self, target: &str) -> Resolution {
+        let target = target.trim();
+        if target.contains('/') {
+            return self.resolve_path(target);
+        }
+
+        let key = normalize_id(target);
+        let tiers = [
+            (&self.names, MatchKind::Name),
+            (&self.aliases, MatchKind::Alias),
+            (&self.stems, MatchKind::Stem),
+        ];
+        for (table, matched_by) in tiers {
+            if let Some(ids) = table.get(&key) {
+                return Self::pick(ids.clone(), matched_by);
+            }
+        }
+        Resolution::Missing
+    }
+
+    fn resolve_path(&self, target: &str) -> Resolution {
+        let target = normalize_path(target);
+        if target.is_empty() {
+            return Resolution::Missing;
+        }
+        let mut ids: Vec<String> = Vec::new();
+        for (path, id) in &self.paths {
+            let matches = path
+                .strip_suffix(target.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.ends_with('/'));
+            if matches && !ids.contains(id) {
+                ids.push(id.clone());
+            }
+        }
+        if ids.is_empty() {
+            Resolution::Missing
+        } else {
+            Self::pick(ids, MatchKind::Path)
+        }
+    }
+
+    fn pick(mut ids: Vec<String>, matched_by: MatchKind) -> Resolution {
+        if ids.len() == 1 {
+            Resolution::Found {
+                id: ids.remove(0),
+                matched_by,
+            }
+        } else {
+            ids.sort();
+            Resolution::Ambiguous {
+                candidates: ids,
+                matched_by,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph_builder::graph_node_ids;
+    use crate::test_support::skill_entry;
+
+    #[test]
+    fn test_resolves_by_tier_and_reports_ambiguity() {
+        let skills = [
+            skill_entry(
+                "/ws/data/load-csv.md",
+                "CSV Loader",
+                serde_json::json!({"aliases": ["loader"], "slug": "csv"}),
+            ),
+            skill_entry(
+                "/ws/py/pandas.md",
+                "Pandas Helper",
+                serde_json::json!({"aliases": ["Loader"]}),
+            ),
+            skill_entry("/ws/pdf-tools/SKILL.md", "PDF", serde_json::json!({})),
+            skill_entry("/ws/other/load-csv.md", "Loader", serde_json::json!({})),
+        ];
+        let resolver = SkillResolver::new(&skills, &graph_node_ids(&skills).0);
+
+        let found = |id: &str, matched_by| Resolution::Found {
+            id: id.to_string(),
+            matched_by,
+        };
+        assert_eq!(
+            resolver.resolve("csv loader"),
+            found("csv-loader", MatchKind::Name)
+        );
+        // A name beats the aliases that would otherwise be ambiguous
+        assert_eq!(resolver.resolve("loader"), found("loader", MatchKind::Name));
+        assert_eq!(
+            resolver.resolve("CSV"),
+            found("csv-loader", MatchKind::Alias)
+        );
+        assert_eq!(
+            resolver.resolve("pandas"),
+            found("pandas-helper", MatchKind::Stem)
+        );
+        assert_eq!(resolver.resolve("pdf-tools"), found("pdf", MatchKind::Stem));
+        assert_eq!(
+            resolver.resolve("./data/load-csv.md"),
+            found("csv-loader", MatchKind::Path)
+        );
+        assert_eq!(
+            resolver.resolve("pdf-tools/"),
+            found("pdf", MatchKind::Path)
+        );
+        assert_eq!(resolver.resolve("ata/load-csv"), Resolution::Missing);
+        assert_eq!(
+            resolver.resolve("load-csv"),
+            Resolution::Ambiguous {
+                candidates: vec!["csv-loader".to_string(), "loader".to_string()],
+                matched_by: MatchKind::Stem,
+            }
+        );
+        assert_eq!(resolver.resolve("nothing"), Resolution::Missing);
+
+        // Relative paths start from the linking skill's directory
+        assert_eq!(
+            resolver.resolve_from("../data/load-csv", "/ws/py/pandas.md"),
+            found("csv-loader", MatchKind::Path)
+        );
+        assert_eq!(
+            resolver.resolve_from("./load-csv.md", "/ws/other/notes.md"),
+            found("loader", MatchKind::Path)
+        );
+        assert_eq!(
+            resolver.resolve_from("../load-csv.md", "/ws/other/notes.md"),
+            Resolution::Missing
+        );
+    }
+}
@@ -0,0 +1,188 @@
+//! graph_schema.rs — Which frontmatter keys become graph edges.
+//!
+//! A workspace may declare its own edge types in `.skills.toml` at its root:
+//!
+//! ```toml
+//! [graph.edges.depends_on]
+//! kind = "depends"        # edge kind; defaults to the key
+//! direction = "outgoing"  # or "incoming": the target links to this skill
+//! style = "dashed"        # hint for renderers: solid, dashed, dotted, …
+//! target = "skill"        # or "file": values are workspace paths
+//! node_type = "doc"       # node type for file targets; defaults to "file"
+//! ```
+//!
+//! `related` (skills, dashed) and `scripts` (files, dotted, shown as
+//! `script` nodes) are always mapped unless the file redefines them. Body
+//! wiki-links are not configurable; they are always `wiki` edges.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+pub const WORKSPACE_CONFIG_FILE: &str = ".skills.toml";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EdgeDirection {
+    #[default]
+    Outgoing,
+    Incoming,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EdgeTarget {
+    #[default]
+    Skill,
+    File,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EdgeSchema {
+    #[serde(default)]
+    pub kind: Option<String>,
+    #[serde(default)]
+    pub direction: EdgeDirection,
+    #[serde(default)]
+    pub style: Option<String>,
+    #[serde(default)]
+    pub target: EdgeTarget,
+    #[serde(default)]
+    pub node_type: Option<String>,
+}
+
+impl EdgeSchema {
+    /// Edge kind for values of frontmatter `key`.
+    pub fn kind_for<'a>(&'a self, key: &'a str) -> &'a str {
+        self.kind.as_deref().unwrap_or(key)
+    }
+
+    /// Node type, and id prefix, of file targets.
+    pub fn file_node_type(&self) -> &str {
+        self.node_type.as_deref().unwrap_or("file")
+    }
+}
+
+/// Frontmatter key → edge schema, in key order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphSchema {
+    pub edges: BTreeMap<String, EdgeSchema>,
+}
+
+impl Default for GraphSchema {
+    fn default() -> Self {
+        let mut edges = BTreeMap::new();
+        edges.insert(
+            "related".to_string(),
+            EdgeSchema {
+                style: Some("dashed".to_string()),
+                ..EdgeSchema::default()
+            },
+        );
+        edges.insert(
+            "scripts".to_string(),
+            EdgeSchema {
+                style: Some("dotted".to_string()),
+                target: EdgeTarget::File,
+                node_type: Some("script".to_string()),
+                ..EdgeSchema::default()
+            },
+        );
+        GraphSchema { edges }
+    }
+}
+
+impl GraphSchema {
+    /// Parse the `[graph.edges]` section of a `.skills.toml`, on top of the
+    /// built-in mappings.
+    pub fn from_toml(raw: &str) -> Result<Self, String> {
+        let table: toml::Table = toml::from_str(raw)
+            .map_err(|e| format!("Invalid {}: {}", WORKSPACE_CONFIG_FILE, e.message()))?;
+        let mut schema = GraphSchema::default();
+
+        let Some(edges) = table.get("graph").and_then(|graph| graph.get("edges")) else {
+            return Ok(schema);
+        };
+        let edges = edges
+            .as_table()
+            .ok_or_else(|| format!("{}: graph.edges must be a table", WORKSPACE_CONFIG_FILE))?;
+        for (key, value) in edges {
+            let edge: EdgeSchema = value.clone().try_into().map_err(|e: toml::de::Error| {
+                format!(
+                    "{}: graph.edges.{}: {}",
+                    WORKSPACE_CONFIG_FILE,
+                    key,
+                    e.message()
+                )
+            })?;
+            schema.edges.insert(key.clone(), edge);
+        }
+        Ok(schema)
+    }
+
+    /// Schema for the workspace at `root`; the built-ins when it has no
+    /// `.skills.toml`.
+    pub fn load(root: &Path) -> Result<Self, String> {
+        let path = root.join(WORKSPACE_CONFIG_FILE);
+        if !path.is_file() {
+            return Ok(GraphSchema::default());
+        }
+        let raw = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read {}: {}", WORKSPACE_CONFIG_FILE, e))?;
+        Self::from_toml(&raw)
+    }
+
+    /// Style hint per edge kind, including `wiki`.
+    pub fn edge_styles(&self) -> BTreeMap<String, String> {
+        let mut styles = BTreeMap::from([("wiki".to_string(), "solid".to_string())]);
+        for (key, edge) in &self.edges {
+            if let Some(style) = &edge.style {
+                styles.insert(edge.kind_for(key).to_string(), style.clone());
+            }
+        }
+        styles
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_toml_merges_with_builtins() {
+        let schema = GraphSchema::from_toml(
+            r#"
+[graph.edges.depends_on]
+kind = "depends"
+style = "bold"
+
+[graph.edges.supersedes]
+direction = "incoming"
+
+[graph.edges.docs]
+target = "file"
+node_type = "doc"
+"#,
+        )
+        .unwrap();
+
+        let keys: Vec<&str> = schema.edges.keys().map(|k| k.as_str()).collect();
+        assert_eq!(
+            keys,
+            vec!["depends_on", "docs", "related", "scripts", "supersedes"]
+        );
+        assert_eq!(schema.edges["depends_on"].kind_for("depends_on"), "depends");
+        assert_eq!(
+            schema.edges["supersedes"].direction,
+            EdgeDirection::Incoming
+        );
+        assert_eq!(schema.edges["docs"].file_node_type(), "doc");
+        assert_eq!(schema.edge_styles()["depends"], "bold");
+        assert_eq!(schema.edge_styles()["related"], "dashed");
+
+        let error =
+            GraphSchema::from_toml("[graph.edges.calls]\ntarget = \"module\"\n").unwrap_err();
+        assert!(error.contains("graph.edges.calls"));
+    }
+}
@@ -0,0 +1,216 @@
+//! graph_scripts.rs — Check the files that skills link to as graph nodes.
+//!
+//! A file target such as a `scripts[]` entry is looked up next to the skill
+//! file first, then at the workspace root. Its node id uses the
+//! workspace-relative path, so two skills pointing at the same script share
+//! one node; a target that is not found keeps the path as written.
+//!
+//! Both the skill paths and the targets come from the frontend, so only
+//! files that resolve inside the open workspace are looked at. Anything
+//! outside it, and every target when there is no workspace, is reported as
+//! broken without being stat'ed or opened.
+//!
+//! Scripts are files below a `scripts/` or `bin/` directory, the same rule
+//! bundles use. Those that no skill links to are reported as orphans.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+
+use crate::fs_scan::workspace_files;
+
+/// Longest first line read when looking for a shebang.
+const MAX_SHEBANG_BYTES: u64 = 256;
+
+/// What is on disk behind a file node.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FileInfo {
+    /// Absolute path the target resolved to, when it exists.
+    pub resolved_path: Option<String>,
+    pub exists: bool,
+    pub executable: bool,
+    /// Program named by the shebang line, e.g. `python3` or `bash`.
+    pub interpreter: Option<String>,
+    pub size: Option<u64>,
+}
+
+/// Program a `#!` line runs, looking through `/usr/bin/env` and its flags.
+pub fn parse_shebang(line: &str) -> Option<String> {
+    let command = line.strip_prefix("#!")?.trim();
+    let mut words = command.split_whitespace();
+    let program = words.next()?;
+    let program = program.rsplit('/').next().unwrap_or(program);
+    if program != "env" {
+        return Some(program.to_string());
+    }
+    words
+        .find(|word| !word.starts_with('-') && !word.contains('='))
+        .map(|word| word.to_string())
+}
+
+fn read_interpreter(path: &Path) -> Option<String> {
+    let file = std::fs::File::open(path).ok()?;
+    let mut line = String::new();
+    BufReader::new(file)
+        .take(MAX_SHEBANG_BYTES)
+        .read_line(&mut line)
+        .ok()?;
+    parse_shebang(line.trim_end())
+}
+
+#[cfg(unix)]
+fn is_executable(metadata: &std::fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(_metadata: &std::fs::Metadata) -> bool {
+    false
+}
+
+/// Find `target` as written by the skill at `skill_path` inside `root`,
+/// returning the path to use in its node id and what was found.
+pub fn resolve_file_target(
+    target: &str,
+    skill_path: &str,
+    root: Option<&Path>,
+) -> (String, FileInfo) {
+    let Some(root) = root else {
+        return (target.to_string(), FileInfo::default());
+    };
+    let written = Path::new(target);
+    let candidates: Vec<PathBuf> = if written.is_absolute() {
+        vec![written.to_path_buf()]
+    } else {
+        Path::new(skill_path)
+            .parent()
+            .into_iter()
+            .chain([root])
+            .map(|dir| dir.join(written))
+            .collect()
+    };
+
+    let Some((found, metadata)) = candidates.into_iter().find_map(|path| {
+        let path = path.canonicalize().ok().filter(|p| p.starts_with(root))?;
+        let metadata = std::fs::metadata(&path).ok().filter(|m| m.is_file())?;
+        Some((path, metadata))
+    }) else {
+        return (target.to_string(), FileInfo::default());
+    };
+
+    let id_path = found
+        .strip_prefix(root)
+        .map(|relative| relative.to_string_lossy().replace('\\', "/"))
+        .unwrap_or_else(|_| target.to_string());
+    let info = FileInfo {
+        resolved_path: Some(found.to_string_lossy().to_string()),
+        exists: true,
+        executable: is_executable(&metadata),
+        interpreter: read_interpreter(&found),
+        size: Some(metadata.len()),
+    };
+    (id_path, info)
+}
+
+fn is_script_path(relative: &Path) -> bool {
+    relative
+        .parent()
+        .into_iter()
+        .flat_map(|dir| dir.components())
+        .any(|part| matches!(part.as_os_str().to_str(), Some("scripts") | Some("bin")))
+}
+
+/// Workspace-relative paths of scripts under `root` not in `referenced`
+/// (absolute, canonical paths), sorted.
+pub fn orphan_scripts(root: &Path, referenced: &HashSet<PathBuf>) -> Result<Vec<String>, String> {
+    Ok(workspace_files(root)?
+        .into_iter()
+        .filter_map(|path| {
+            let relative = path.strip_prefix(root).ok()?;
+            if !is_script_path(relative) {
+                return None;
+            }
+            let canonical = path.canonicalize().unwrap_or_else(|_| path.clone());
+            if referenced.contains(&canonical) {
+                return None;
+            }
+            Some(relative.to_string_lossy().replace('\\', "/"))
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    #[test]
+    fn test_parse_shebang() {
+        assert_eq!(parse_shebang("#!/bin/bash"), Some("bash".to_string()));
+        assert_eq!(
+            parse_shebang("#!/usr/bin/env -S PYTHONUNBUFFERED=1 python3 -u"),
+            Some("python3".to_string())
+        );
+        assert_eq!(
+            parse_shebang("#! /usr/bin/env node"),
+            Some("node".to_string())
+        );
+        assert_eq!(parse_shebang("echo hi"), None);
+    }
+
+    #[test]
+    fn test_resolves_scripts_and_finds_orphans() {
+        let dir = TempDir::new("scripts");
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("tools/scripts")).unwrap();
+        std::fs::create_dir_all(root.join("scripts")).unwrap();
+        std::fs::write(
+            root.join("tools/scripts/run.py"),
+            "#!/usr/bin/env python3\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("scripts/shared.sh"), "echo\n").unwrap();
+        std::fs::write(root.join("scripts/unused.sh"), "echo\n").unwrap();
+        let skill = root.join("tools/tool.md").to_string_lossy().to_string();
+
+        let (id_path, info) = resolve_file_target("./scripts/run.py", &skill, Some(root));
+        assert_eq!(id_path, "tools/scripts/run.py");
+        assert!(info.exists);
+        assert_eq!(info.interpreter.as_deref(), Some("python3"));
+        assert_eq!(info.size, Some(23));
+
+        // Falls back to the workspace root
+        let (id_path, shared) = resolve_file_target("scripts/shared.sh", &skill, Some(root));
+        assert_eq!(id_path, "scripts/shared.sh");
+
+        let (id_path, missing) = resolve_file_target("scripts/gone.sh", &skill, Some(root));
+        assert_eq!(id_path, "scripts/gone.sh");
+        assert!(!missing.exists);
+
+        // Nothing outside the workspace, or without one, is looked at
+        let outside_dir = TempDir::new("scripts-outside");
+        let outside = outside_dir.join("outside.sh");
+        std::fs::write(&outside, "#!/bin/sh\n").unwrap();
+        let escaping = format!(
+            "../../{}/outside.sh",
+            outside_dir.path().file_name().unwrap().to_string_lossy()
+        );
+        for target in [escaping.as_str(), &outside.to_string_lossy()] {
+            let (_, leaked) = resolve_file_target(target, &skill, Some(root));
+            assert!(!leaked.exists && leaked.interpreter.is_none() && leaked.size.is_none());
+        }
+        let (_, unrooted) = resolve_file_target("./scripts/run.py", &skill, None);
+        assert!(!unrooted.exists);
+
+        let referenced: HashSet<PathBuf> = [info, shared]
+            .iter()
+            .filter_map(|info| info.resolved_path.as_ref().map(PathBuf::from))
+            .collect();
+        assert_eq!(
+            orphan_scripts(root, &referenced).unwrap(),
+            vec!["scripts/unused.sh"]
+        );
+    }
+}
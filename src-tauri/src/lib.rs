@@ -1,9 +1,29 @@
 mod autogit;
 mod file_watch;
+mod frontmatter_patch;
 mod fs_scan;
 mod git_reader;
 mod graph_builder;
+mod graph_cycles;
+mod graph_export;
+mod graph_metrics;
+mod graph_query;
+mod graph_resolver;
+mod graph_schema;
+mod graph_scripts;
+mod scan_cache;
+mod search_index;
+mod skill_bundle;
+mod skill_document;
+mod skill_query;
+mod skill_rename;
+mod skill_templates;
+mod skill_trash;
+mod skill_write;
+#[cfg(test)]
+mod test_support;
 mod theme_config;
+mod workspace;
 
 use tauri::Manager;
 
@@ -11,8 +31,12 @@ use tauri::Manager;
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_fs::init())
+        .plugin(tauri_plugin_dialog::init())
         .manage(autogit::AutogitDaemonManager::default())
         .manage(file_watch::DirectoryWatcherManager::default())
+        .manage(fs_scan::ScanManager::default())
+        .manage(search_index::SearchManager::default())
+        .manage(workspace::WorkspaceRegistry::default())
         .on_window_event(|window, event| {
             if matches!(event, tauri::WindowEvent::Destroyed) {
                 let state = window.state::<file_watch::DirectoryWatcherManager>();
@@ -22,9 +46,28 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             fs_scan::scan_folder,
             fs_scan::scan_folder_index,
+            fs_scan::scan_folder_index_stream,
+            fs_scan::cancel_scan,
             fs_scan::read_skill_file,
             fs_scan::write_skill_file,
+            frontmatter_patch::update_frontmatter,
+            search_index::search_skills,
+            skill_query::query_skills,
+            skill_templates::create_skill_file,
+            skill_rename::rename_skill,
+            skill_trash::delete_skill,
+            skill_trash::list_trash,
+            skill_trash::restore_trash_item,
+            skill_trash::purge_trash,
+            skill_templates::list_skill_templates,
             graph_builder::build_graph,
+            graph_query::graph_dependencies,
+            graph_query::graph_dependents,
+            graph_query::graph_shortest_path,
+            graph_query::graph_all_paths,
+            graph_query::graph_impact,
+            graph_export::export_graph,
+            graph_metrics::graph_metrics,
             theme_config::save_theme_config,
             theme_config::load_theme_config,
             autogit::start_autogit_daemon,
@@ -37,6 +80,8 @@ pub fn run() {
             git_reader::git_diff,
             file_watch::watch_directory,
             file_watch::unwatch_directory,
+            workspace::open_workspace,
+            workspace::close_workspace,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
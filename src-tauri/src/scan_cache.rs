@@ -0,0 +1,248 @@
+//! scan_cache.rs — Persistent on-disk cache for the index scan.
+//!
+//! `scan_folder_index` consults this cache so that only files whose
+//! mtime/size (and, failing that, content hash) changed are re-parsed.
+//! The cache lives in `.git/` when the scanned folder is a repository root,
+//! otherwise under the app data dir, one file per canonical workspace root.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs::Metadata;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use crate::fs_scan::{ScanDiagnostic, SkillIndex};
+use crate::skill_write::write_atomic;
+
+const CACHE_FILE_NAME: &str = "fv-skills-index.json";
+const CACHE_DIR_NAME: &str = "scan-cache";
+// Bump whenever `SkillIndex` or the parse rules change so stale entries are
+// discarded instead of being served as hits.
+const SCAN_CACHE_VERSION: u32 = 4;
+
+// ---------------------------------------------------------------------------
+// Public types
+// ---------------------------------------------------------------------------
+
+/// Which entries of an index scan came from the cache.
+///
+/// All paths are canonical (symlinks resolved) since that is the cache key.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CacheReport {
+    /// Files served from the cache without re-parsing.
+    pub hits: Vec<String>,
+    /// Files that were new or changed and had to be parsed.
+    pub updated: Vec<String>,
+    /// Cached files that no longer exist in the workspace.
+    pub deleted: Vec<String>,
+}
+
+/// One cached file. `skill` is `None` for files that were read but rejected
+/// (no frontmatter, malformed YAML, non-UTF-8), in which case `diagnostic`
+/// says why, so those are not re-read on every open either.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedEntry {
+    /// Modification time in milliseconds since the Unix epoch.
+    pub mtime: u64,
+    pub size: u64,
+    /// Hex-encoded SHA-256 of the file content.
+    pub hash: String,
+    pub skill: Option<SkillIndex>,
+    pub diagnostic: Option<ScanDiagnostic>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheStatus {
+    Hit,
+    Updated,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CacheFile {
+    version: u32,
+    entries: BTreeMap<String, CachedEntry>,
+}
+
+pub struct ScanCache {
+    path: Option<PathBuf>,
+    entries: BTreeMap<String, CachedEntry>,
+}
+
+// ---------------------------------------------------------------------------
+// Cache lifecycle
+// ---------------------------------------------------------------------------
+
+impl ScanCache {
+    /// Load the cache for `root`. A missing, unreadable or outdated cache
+    /// file yields an empty cache rather than an error.
+    pub fn load(root: &Path, app_data_dir: Option<PathBuf>) -> Self {
+        let path = cache_path_for(root, app_data_dir);
+        let entries = path
+            .as_ref()
+            .and_then(|p| std::fs::read_to_string(p).ok())
+            .and_then(|raw| serde_json::from_str::<CacheFile>(&raw).ok())
+            .filter(|file| file.version == SCAN_CACHE_VERSION)
+            .map(|file| file.entries)
+            .unwrap_or_default();
+
+        Self { path, entries }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&CachedEntry> {
+        self.entries.get(key)
+    }
+
+    /// Replace the cache contents with the entries seen in this scan and
+    /// return the keys that were dropped.
+    ///
+    /// When `complete` is false (a cancelled scan, or one narrowed by
+    /// include/exclude globs) unseen entries are kept and nothing is
+    /// reported as deleted.
+    pub fn replace(&mut self, seen: BTreeMap<String, CachedEntry>, complete: bool) -> Vec<String> {
+        let mut deleted = Vec::new();
+        if complete {
+            deleted = self
+                .entries
+                .keys()
+                .filter(|key| !seen.contains_key(*key))
+                .cloned()
+                .collect();
+            self.entries = seen;
+        } else {
+            self.entries.extend(seen);
+        }
+        deleted
+    }
+
+    /// Persist the cache with `write_atomic`, so neither a crash nor a
+    /// concurrent scan of the same workspace leaves a half-written cache.
+    pub fn save(&self) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("create cache dir {}: {e}", parent.display()))?;
+        }
+
+        let payload = serde_json::to_string(&CacheFile {
+            version: SCAN_CACHE_VERSION,
+            entries: self.entries.clone(),
+        })
+        .map_err(|e| format!("serialize scan cache: {e}"))?;
+
+        write_atomic(path, payload.as_bytes())
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Decide whether `cached` is still valid for the file described by
+/// `metadata`, reading `content` only when the cheap mtime/size check fails.
+///
+/// Returns the refreshed entry on a hit (mtime may have moved while the
+/// content stayed identical), or `None` when the file must be re-parsed.
+pub fn revalidate(
+    cached: &CachedEntry,
+    metadata: &Metadata,
+    content: impl FnOnce() -> Option<Vec<u8>>,
+) -> Option<CachedEntry> {
+    let mtime = mtime_millis(metadata);
+    let size = metadata.len();
+    if cached.mtime == mtime && cached.size == size {
+        return Some(cached.clone());
+    }
+
+    let bytes = content()?;
+    if content_hash(&bytes) != cached.hash {
+        return None;
+    }
+
+    Some(CachedEntry {
+        mtime,
+        size,
+        ..cached.clone()
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Hex-encoded SHA-256 of `bytes`.
+pub fn content_hash(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+pub fn mtime_millis(metadata: &Metadata) -> u64 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Cache key for a file: its canonical path, falling back to the path as
+/// walked when canonicalization fails (e.g. the file vanished mid-scan).
+pub fn cache_key(path: &Path) -> String {
+    std::fs::canonicalize(path)
+        .unwrap_or_else(|_| path.to_path_buf())
+        .to_string_lossy()
+        .to_string()
+}
+
+fn cache_path_for(root: &Path, app_data_dir: Option<PathBuf>) -> Option<PathBuf> {
+    let canonical_root = std::fs::canonicalize(root).unwrap_or_else(|_| root.to_path_buf());
+
+    let git_dir = canonical_root.join(".git");
+    if git_dir.is_dir() {
+        return Some(git_dir.join(CACHE_FILE_NAME));
+    }
+
+    let root_hash = content_hash(canonical_root.to_string_lossy().as_bytes());
+    app_data_dir.map(|dir| {
+        dir.join(CACHE_DIR_NAME)
+            .join(format!("{}.json", &root_hash[..16]))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(hash: &str) -> CachedEntry {
+        CachedEntry {
+            mtime: 1,
+            size: 1,
+            hash: hash.to_string(),
+            skill: None,
+            diagnostic: None,
+        }
+    }
+
+    #[test]
+    fn test_content_hash_is_stable_hex() {
+        let hash = content_hash(b"---\nname: a\n---\n");
+        assert_eq!(hash.len(), 64);
+        assert_eq!(hash, content_hash(b"---\nname: a\n---\n"));
+        assert_ne!(hash, content_hash(b"---\nname: b\n---\n"));
+    }
+
+    #[test]
+    fn test_replace_reports_deleted_only_when_complete() {
+        let mut cache = ScanCache {
+            path: None,
+            entries: BTreeMap::from([("a".to_string(), entry("1")), ("b".to_string(), entry("2"))]),
+        };
+
+        let seen = BTreeMap::from([("a".to_string(), entry("1"))]);
+        assert!(cache.replace(seen.clone(), false).is_empty());
+        assert!(cache.get("b").is_some());
+
+        assert_eq!(cache.replace(seen, true), vec!["b".to_string()]);
+        assert!(cache.get("b").is_none());
+    }
+}
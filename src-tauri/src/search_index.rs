@@ -0,0 +1,968 @@
+//! search_index.rs — Full-text search over skill names, descriptions, tags
+//! and bodies.
+//!
+//! Each workspace gets an in-memory inverted index (term → document →
+//! per-field positions) ranked with BM25. Queries are whitespace-separated
+//! clauses, all of which must match:
+//!
+//!   - `pandas`           term, any field
+//!   - `"data frame"`     phrase (consecutive terms within one field)
+//!   - `pand*`            prefix
+//!   - `name:pandas`      term restricted to `name`, `description`, `tag` or `body`
+//!   - `type:subagent`    any other `key:value` filters on frontmatter
+//!   - `-legacy`          negation of any of the above
+//!
+//! The index is built on the first search and kept up to date by re-checking
+//! mtime/size of the workspace files, re-parsing only those that changed.
+//! The directory watcher marks a workspace as changed so the next search
+//! picks edits up immediately.
+//!
+//! Tauri commands exposed:
+//!   - `search_skills` → ranked hits with highlighted snippets
+
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::State;
+
+use crate::fs_scan::{
+    derive_name, extract_frontmatter, fallback_skill_name, workspace_skill_files,
+    MAX_SKILL_FILE_BYTES,
+};
+use crate::scan_cache::mtime_millis;
+use crate::workspace::WorkspaceRegistry;
+
+// BM25 parameters (the usual defaults).
+const BM25_K1: f64 = 1.2;
+const BM25_B: f64 = 0.75;
+// Without a watcher running, files are re-checked at most this often.
+const SEARCH_REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+const DEFAULT_SEARCH_LIMIT: usize = 50;
+const MAX_SEARCH_LIMIT: usize = 500;
+// A short prefix like `a*` would otherwise expand to most of the vocabulary.
+const MAX_PREFIX_EXPANSIONS: usize = 64;
+const SNIPPET_TOKENS: usize = 24;
+const SNIPPET_LEAD_TOKENS: usize = 4;
+
+const FIELD_COUNT: usize = 4;
+
+// ---------------------------------------------------------------------------
+// Public types
+// ---------------------------------------------------------------------------
+
+/// Indexed text fields, in the order they are stored per document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchField {
+    Name,
+    Description,
+    Tags,
+    Body,
+}
+
+impl SearchField {
+    const ALL: [SearchField; FIELD_COUNT] = [
+        SearchField::Name,
+        SearchField::Description,
+        SearchField::Tags,
+        SearchField::Body,
+    ];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    /// Matches in short, curated fields say more than a mention in the body.
+    fn weight(self) -> f64 {
+        match self {
+            SearchField::Name => 3.0,
+            SearchField::Description => 1.5,
+            SearchField::Tags => 2.0,
+            SearchField::Body => 1.0,
+        }
+    }
+
+    fn from_query_key(key: &str) -> Option<Self> {
+        match key {
+            "name" => Some(SearchField::Name),
+            "description" | "desc" => Some(SearchField::Description),
+            "tag" | "tags" => Some(SearchField::Tags),
+            "body" => Some(SearchField::Body),
+            _ => None,
+        }
+    }
+}
+
+/// A run of snippet text; `highlight` marks a matched term.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnippetPart {
+    pub text: String,
+    pub highlight: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchSnippet {
+    pub field: SearchField,
+    pub parts: Vec<SnippetPart>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchHit {
+    pub path: String,
+    /// Path relative to the workspace root, `/`-separated.
+    pub relative_path: String,
+    pub name: String,
+    pub score: f64,
+    pub matched_fields: Vec<SearchField>,
+    /// `None` when the query only had frontmatter filters.
+    pub snippet: Option<SearchSnippet>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchResponse {
+    pub hits: Vec<SearchHit>,
+    /// Number of matching skills before `limit` was applied.
+    pub total: usize,
+    /// Number of skills in the index.
+    pub indexed: usize,
+}
+
+// ---------------------------------------------------------------------------
+// Tokenizing
+// ---------------------------------------------------------------------------
+
+/// Lowercased alphanumeric runs of `text` with their byte ranges.
+fn tokenize(text: &str) -> Vec<(String, Range<usize>)> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    for (i, c) in text.char_indices() {
+        if c.is_alphanumeric() {
+            start.get_or_insert(i);
+        } else if let Some(s) = start.take() {
+            tokens.push((text[s..i].to_lowercase(), s..i));
+        }
+    }
+    if let Some(s) = start {
+        tokens.push((text[s..].to_lowercase(), s..text.len()));
+    }
+    tokens
+}
+
+// ---------------------------------------------------------------------------
+// Documents
+// ---------------------------------------------------------------------------
+
+struct IndexedDoc {
+    path: String,
+    name: String,
+    frontmatter: Option<serde_json::Value>,
+    fields: [String; FIELD_COUNT],
+    lengths: [usize; FIELD_COUNT],
+    mtime: u64,
+    size: u64,
+}
+
+impl IndexedDoc {
+    /// Files without frontmatter are not skills and are left out, as in the
+    /// scan.
+    fn from_content(path: &Path, content: &str, mtime: u64, size: u64) -> Option<Self> {
+        let (parsed, body) = extract_frontmatter(content);
+        let frontmatter = Some(parsed?.0);
+        let name = derive_name(&frontmatter, fallback_skill_name(path));
+        let field = |key: &str| frontmatter.as_ref().and_then(|fm| fm.get(key));
+
+        let description = field("description")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string();
+        let tags = match field("tags") {
+            Some(serde_json::Value::Array(items)) => items
+                .iter()
+                .filter_map(|item| item.as_str())
+                .collect::<Vec<_>>()
+                .join(", "),
+            Some(serde_json::Value::String(tags)) => tags.clone(),
+            _ => String::new(),
+        };
+
+        let fields = [name.clone(), description, tags, body.to_string()];
+        let lengths = fields.clone().map(|text| tokenize(&text).len());
+        Some(Self {
+            path: path.to_string_lossy().to_string(),
+            name,
+            frontmatter,
+            fields,
+            lengths,
+            mtime,
+            size,
+        })
+    }
+
+    fn load(path: &Path) -> Option<Self> {
+        let metadata = std::fs::metadata(path).ok()?;
+        if metadata.len() > MAX_SKILL_FILE_BYTES {
+            return None;
+        }
+        let content = std::fs::read_to_string(path).ok()?;
+        Self::from_content(path, &content, mtime_millis(&metadata), metadata.len())
+    }
+
+    /// `key:value` filter: equal to a scalar, or to any item of a list,
+    /// ignoring case.
+    fn frontmatter_matches(&self, key: &str, value: &str) -> bool {
+        let matches = |v: &serde_json::Value| match v {
+            serde_json::Value::String(s) => s.eq_ignore_ascii_case(value),
+            serde_json::Value::Bool(_) | serde_json::Value::Number(_) => {
+                v.to_string().eq_ignore_ascii_case(value)
+            }
+            _ => false,
+        };
+        match self.frontmatter.as_ref().and_then(|fm| fm.get(key)) {
+            Some(serde_json::Value::Array(items)) => items.iter().any(matches),
+            Some(v) => matches(v),
+            None => false,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Query parsing
+// ---------------------------------------------------------------------------
+
+enum Matcher {
+    /// One term, or a phrase when there are several.
+    Terms(Vec<String>),
+    Prefix(String),
+}
+
+struct TextClause {
+    matcher: Matcher,
+    field: Option<SearchField>,
+    negated: bool,
+}
+
+struct FilterClause {
+    key: String,
+    value: String,
+    negated: bool,
+}
+
+#[derive(Default)]
+struct ParsedQuery {
+    text: Vec<TextClause>,
+    filters: Vec<FilterClause>,
+}
+
+/// One whitespace-separated piece of the query, quotes removed. Shared with
+/// the structured query language in `skill_query`.
+pub(crate) struct RawClause {
+    pub negated: bool,
+    pub key: Option<String>,
+    pub value: String,
+    pub quoted: bool,
+}
+
+pub(crate) fn split_query(query: &str) -> Vec<RawClause> {
+    let mut clauses = Vec::new();
+    let mut chars = query.chars().peekable();
+
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.peek().is_none() {
+            break;
+        }
+
+        let negated = chars.next_if_eq(&'-').is_some();
+        let mut key = None;
+        let mut value = String::new();
+        let mut quoted = false;
+        while let Some(&c) = chars.peek() {
+            if c == '"' {
+                chars.next();
+                quoted = true;
+                value.extend(chars.by_ref().take_while(|&c| c != '"'));
+            } else if c.is_whitespace() {
+                break;
+            } else if c == ':' && key.is_none() && !quoted && !value.is_empty() {
+                chars.next();
+                key = Some(std::mem::take(&mut value).to_lowercase());
+            } else {
+                chars.next();
+                value.push(c);
+            }
+        }
+
+        clauses.push(RawClause {
+            negated,
+            key,
+            value,
+            quoted,
+        });
+    }
+
+    clauses
+}
+
+fn parse_query(query: &str) -> ParsedQuery {
+    let mut parsed = ParsedQuery::default();
+
+    for raw in split_query(query) {
+        let field = match raw.key.as_deref() {
+            None => None,
+            Some(key) => match SearchField::from_query_key(key) {
+                Some(field) => Some(field),
+                None => {
+                    if !raw.value.is_empty() {
+                        parsed.filters.push(FilterClause {
+                            key: key.to_string(),
+                            value: raw.value,
+                            negated: raw.negated,
+                        });
+                    }
+                    continue;
+                }
+            },
+        };
+
+        let prefix = !raw.quoted && raw.value.ends_with('*');
+        let mut terms: Vec<String> = tokenize(raw.value.trim_end_matches('*'))
+            .into_iter()
+            .map(|(term, _)| term)
+            .collect();
+        let matcher = match terms.len() {
+            0 => continue,
+            1 if prefix => Matcher::Prefix(terms.remove(0)),
+            _ => Matcher::Terms(terms),
+        };
+
+        parsed.text.push(TextClause {
+            matcher,
+            field,
+            negated: raw.negated,
+        });
+    }
+
+    parsed
+}
+
+// ---------------------------------------------------------------------------
+// Index
+// ---------------------------------------------------------------------------
+
+/// Positions of one term in one document, per field.
+type Posting = [Vec<u32>; FIELD_COUNT];
+
+/// Per-document result of evaluating one text clause.
+#[derive(Default)]
+struct ClauseMatch {
+    score: f64,
+    fields: HashSet<SearchField>,
+    terms: HashSet<String>,
+}
+
+#[derive(Default)]
+pub struct SearchIndex {
+    docs: Vec<Option<IndexedDoc>>,
+    ids: HashMap<String, usize>,
+    free: Vec<usize>,
+    terms: BTreeMap<String, HashMap<usize, Posting>>,
+    total_lengths: [usize; FIELD_COUNT],
+}
+
+impl SearchIndex {
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    fn doc(&self, path: &str) -> Option<&IndexedDoc> {
+        self.ids.get(path).and_then(|&id| self.docs[id].as_ref())
+    }
+
+    fn insert(&mut self, doc: IndexedDoc) {
+        self.remove(&doc.path);
+
+        let id = match self.free.pop() {
+            Some(id) => id,
+            None => {
+                self.docs.push(None);
+                self.docs.len() - 1
+            }
+        };
+        for field in SearchField::ALL {
+            let f = field.index();
+            for (position, (term, _)) in tokenize(&doc.fields[f]).into_iter().enumerate() {
+                self.terms.entry(term).or_default().entry(id).or_default()[f].push(position as u32);
+            }
+            self.total_lengths[f] += doc.lengths[f];
+        }
+        self.ids.insert(doc.path.clone(), id);
+        self.docs[id] = Some(doc);
+    }
+
+    fn remove(&mut self, path: &str) {
+        let Some(id) = self.ids.remove(path) else {
+            return;
+        };
+        let Some(doc) = self.docs[id].take() else {
+            return;
+        };
+        for field in SearchField::ALL {
+            let f = field.index();
+            for (term, _) in tokenize(&doc.fields[f]) {
+                if let Some(postings) = self.terms.get_mut(&term) {
+                    postings.remove(&id);
+                    if postings.is_empty() {
+                        self.terms.remove(&term);
+                    }
+                }
+            }
+            self.total_lengths[f] -= doc.lengths[f];
+        }
+        self.free.push(id);
+    }
+
+    /// Bring the index in line with the skill files under `root`: drop
+    /// removed files and re-parse those whose mtime or size changed.
+    /// Returns how many documents were added, updated or removed.
+    pub fn refresh(&mut self, root: &Path) -> Result<usize, String> {
+        let files = workspace_skill_files(root)?;
+        let current: HashSet<String> = files
+            .iter()
+            .map(|path| path.to_string_lossy().to_string())
+            .collect();
+
+        let removed: Vec<String> = self
+            .ids
+            .keys()
+            .filter(|path| !current.contains(*path))
+            .cloned()
+            .collect();
+        for path in &removed {
+            self.remove(path);
+        }
+
+        let changed: Vec<&PathBuf> = files
+            .iter()
+            .filter(|path| {
+                let Ok(metadata) = std::fs::metadata(path) else {
+                    return true;
+                };
+                let indexed = self.doc(&path.to_string_lossy());
+                !indexed.is_some_and(|doc| {
+                    doc.mtime == mtime_millis(&metadata) && doc.size == metadata.len()
+                })
+            })
+            .collect();
+        let loaded: Vec<(String, Option<IndexedDoc>)> = changed
+            .par_iter()
+            .map(|path| (path.to_string_lossy().to_string(), IndexedDoc::load(path)))
+            .collect();
+
+        for (path, doc) in loaded {
+            match doc {
+                Some(doc) => self.insert(doc),
+                None => self.remove(&path),
+            }
+        }
+
+        Ok(removed.len() + changed.len())
+    }
+
+    fn bm25(&self, field: SearchField, tf: usize, doc_len: usize, df: usize) -> f64 {
+        let n = self.len() as f64;
+        let df = df as f64;
+        let tf = tf as f64;
+        let avg_len = (self.total_lengths[field.index()] as f64 / n).max(1.0);
+        let idf = (1.0 + (n - df + 0.5) / (df + 0.5)).ln();
+        let norm = tf * (BM25_K1 + 1.0)
+            / (tf + BM25_K1 * (1.0 - BM25_B + BM25_B * doc_len as f64 / avg_len));
+        field.weight() * idf * norm
+    }
+
+    /// Documents containing `terms` consecutively in one field (a single
+    /// term is a phrase of one), scored per field with BM25.
+    fn match_terms(
+        &self,
+        terms: &[String],
+        field: Option<SearchField>,
+    ) -> HashMap<usize, ClauseMatch> {
+        let mut matches = HashMap::new();
+        let Some(postings) = terms
+            .iter()
+            .map(|term| self.terms.get(term))
+            .collect::<Option<Vec<_>>>()
+        else {
+            return matches;
+        };
+
+        let fields: Vec<SearchField> = match field {
+            Some(field) => vec![field],
+            None => SearchField::ALL.to_vec(),
+        };
+
+        // Phrase occurrences per (doc, field), then document frequencies.
+        let mut counts: Vec<(usize, SearchField, usize)> = Vec::new();
+        for &id in postings[0].keys() {
+            let Some(per_term) = postings
+                .iter()
+                .map(|p| p.get(&id))
+                .collect::<Option<Vec<&Posting>>>()
+            else {
+                continue;
+            };
+            for &field in &fields {
+                let f = field.index();
+                let count = per_term[0][f]
+                    .iter()
+                    .filter(|&&start| {
+                        per_term[1..].iter().enumerate().all(|(offset, posting)| {
+                            posting[f]
+                                .binary_search(&(start + offset as u32 + 1))
+                                .is_ok()
+                        })
+                    })
+                    .count();
+                if count > 0 {
+                    counts.push((id, field, count));
+                }
+            }
+        }
+
+        let mut df = [0usize; FIELD_COUNT];
+        for &(_, field, _) in &counts {
+            df[field.index()] += 1;
+        }
+
+        for (id, field, count) in counts {
+            let Some(doc) = self.docs[id].as_ref() else {
+                continue;
+            };
+            let f = field.index();
+            let entry: &mut ClauseMatch = matches.entry(id).or_default();
+            entry.score += self.bm25(field, count, doc.lengths[f], df[f]);
+            entry.fields.insert(field);
+            entry.terms.extend(terms.iter().cloned());
+        }
+        matches
+    }
+
+    fn match_clause(&self, clause: &TextClause) -> HashMap<usize, ClauseMatch> {
+        match &clause.matcher {
+            Matcher::Terms(terms) => self.match_terms(terms, clause.field),
+            Matcher::Prefix(prefix) => {
+                // Best-scoring expansion per document, so a prefix matching
+                // many word forms does not outrank an exact term.
+                let mut merged: HashMap<usize, ClauseMatch> = HashMap::new();
+                let expansions = self
+                    .terms
+                    .range(prefix.clone()..)
+                    .take_while(|(term, _)| term.starts_with(prefix.as_str()))
+                    .take(MAX_PREFIX_EXPANSIONS);
+                for (term, _) in expansions {
+                    for (id, found) in self.match_terms(std::slice::from_ref(term), clause.field) {
+                        let entry = merged.entry(id).or_default();
+                        entry.score = entry.score.max(found.score);
+                        entry.fields.extend(found.fields);
+                        entry.terms.extend(found.terms);
+                    }
+                }
+                merged
+            }
+        }
+    }
+
+    pub fn search(&self, root: &Path, query: &str, limit: usize) -> SearchResponse {
+        let parsed = parse_query(query);
+
+        let mut candidates: Option<HashMap<usize, ClauseMatch>> = None;
+        let mut excluded: HashSet<usize> = HashSet::new();
+        for clause in &parsed.text {
+            let found = self.match_clause(clause);
+            if clause.negated {
+                excluded.extend(found.into_keys());
+                continue;
+            }
+            candidates = Some(match candidates {
+                None => found,
+                Some(mut current) => {
+                    current.retain(|id, _| found.contains_key(id));
+                    for (id, entry) in current.iter_mut() {
+                        let other = &found[id];
+                        entry.score += other.score;
+                        entry.fields.extend(other.fields.iter().copied());
+                        entry.terms.extend(other.terms.iter().cloned());
+                    }
+                    current
+                }
+            });
+        }
+
+        // Only filters or negations: start from every document.
+        let candidates = candidates.unwrap_or_else(|| {
+            self.ids
+                .values()
+                .map(|&id| (id, ClauseMatch::default()))
+                .collect()
+        });
+
+        let mut hits: Vec<(f64, &IndexedDoc, ClauseMatch)> = candidates
+            .into_iter()
+            .filter(|(id, _)| !excluded.contains(id))
+            .filter_map(|(id, found)| Some((found.score, self.docs[id].as_ref()?, found)))
+            .filter(|(_, doc, _)| {
+                parsed.filters.iter().all(|filter| {
+                    doc.frontmatter_matches(&filter.key, &filter.value) != filter.negated
+                })
+            })
+            .collect();
+        hits.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.1.path.cmp(&b.1.path)));
+
+        let total = hits.len();
+        let hits = hits
+            .into_iter()
+            .take(limit)
+            .map(|(score, doc, found)| {
+                let relative_path = Path::new(&doc.path)
+                    .strip_prefix(root)
+                    .map(|p| p.to_string_lossy().replace('\\', "/"))
+                    .unwrap_or_else(|_| doc.path.clone());
+                let mut matched_fields: Vec<SearchField> = found.fields.into_iter().collect();
+                matched_fields.sort_by_key(|field| field.index());
+                SearchHit {
+                    path: doc.path.clone(),
+                    relative_path,
+                    name: doc.name.clone(),
+                    score,
+                    snippet: best_snippet(doc, &matched_fields, &found.terms),
+                    matched_fields,
+                }
+            })
+            .collect();
+
+        SearchResponse {
+            hits,
+            total,
+            indexed: self.len(),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Snippets
+// ---------------------------------------------------------------------------
+
+/// Snippet from the body when it matched (it has the most context),
+/// otherwise from the first matched field.
+fn best_snippet(
+    doc: &IndexedDoc,
+    matched_fields: &[SearchField],
+    terms: &HashSet<String>,
+) -> Option<SearchSnippet> {
+    let field = if matched_fields.contains(&SearchField::Body) {
+        SearchField::Body
+    } else {
+        *matched_fields.first()?
+    };
+    let parts = snippet_parts(&doc.fields[field.index()], terms)?;
+    Some(SearchSnippet { field, parts })
+}
+
+/// Markdown line breaks and indentation read as single spaces in a snippet.
+fn collapse_whitespace(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut in_space = false;
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if !in_space {
+                out.push(' ');
+            }
+            in_space = true;
+        } else {
+            out.push(c);
+            in_space = false;
+        }
+    }
+    out
+}
+
+/// Cut a window of `SNIPPET_TOKENS` tokens around the densest cluster of
+/// matched terms and split it into plain and highlighted parts.
+fn snippet_parts(text: &str, terms: &HashSet<String>) -> Option<Vec<SnippetPart>> {
+    let tokens = tokenize(text);
+    let hits: Vec<usize> = (0..tokens.len())
+        .filter(|&i| terms.contains(&tokens[i].0))
+        .collect();
+
+    let best = hits
+        .iter()
+        .max_by_key(|&&start| {
+            let in_window = hits
+                .iter()
+                .filter(|&&i| i >= start && i < start + SNIPPET_TOKENS)
+                .count();
+            (in_window, std::cmp::Reverse(start))
+        })
+        .copied()?;
+    let first = best.saturating_sub(SNIPPET_LEAD_TOKENS);
+    let last = (first + SNIPPET_TOKENS).min(tokens.len()) - 1;
+
+    let start = if first == 0 { 0 } else { tokens[first].1.start };
+    let end = if last + 1 == tokens.len() {
+        text.len()
+    } else {
+        tokens[last].1.end
+    };
+
+    let mut parts: Vec<SnippetPart> = Vec::new();
+    let mut push = |text: &str, highlight: bool| {
+        let text = if highlight {
+            text.to_string()
+        } else {
+            collapse_whitespace(text)
+        };
+        match parts.last_mut() {
+            Some(last) if last.highlight == highlight => last.text.push_str(&text),
+            _ => parts.push(SnippetPart { text, highlight }),
+        }
+    };
+
+    if start > 0 {
+        push("…", false);
+    }
+    let mut cursor = start;
+    for (term, range) in &tokens[first..=last] {
+        if terms.contains(term) {
+            if range.start > cursor {
+                push(&text[cursor..range.start], false);
+            }
+            push(&text[range.clone()], true);
+            cursor = range.end;
+        }
+    }
+    if end > cursor {
+        push(&text[cursor..end], false);
+    }
+    if end < text.len() {
+        push("…", false);
+    }
+
+    Some(parts)
+}
+
+// ---------------------------------------------------------------------------
+// Managed state
+// ---------------------------------------------------------------------------
+
+struct WorkspaceIndex {
+    index: SearchIndex,
+    /// `None` until the first refresh.
+    refreshed_at: Option<Instant>,
+}
+
+/// One workspace's index behind its own lock, so refreshing it never holds
+/// up searches in other workspaces.
+#[derive(Default)]
+struct WorkspaceSearch {
+    /// Set by watcher events; kept outside the lock so the watcher never
+    /// waits for a refresh in progress.
+    stale: AtomicBool,
+    state: Mutex<Option<WorkspaceIndex>>,
+}
+
+/// Search indexes keyed by canonical workspace root.
+#[derive(Default)]
+pub struct SearchManager {
+    workspaces: Mutex<HashMap<PathBuf, Arc<WorkspaceSearch>>>,
+}
+
+/// Watcher paths may go through symlinks while roots are canonical. A
+/// removed file cannot be canonicalized, so its directory is instead.
+fn canonical_event_path(path: &Path) -> PathBuf {
+    if let Ok(canonical) = path.canonicalize() {
+        return canonical;
+    }
+    match (path.parent().map(Path::canonicalize), path.file_name()) {
+        (Some(Ok(dir)), Some(name)) => dir.join(name),
+        _ => path.to_path_buf(),
+    }
+}
+
+impl SearchManager {
+    /// Called by the directory watcher: workspaces containing any of `paths`
+    /// are re-checked on the next search instead of after the interval.
+    pub fn mark_changed(&self, paths: &[PathBuf]) {
+        let paths: Vec<PathBuf> = paths.iter().map(|p| canonical_event_path(p)).collect();
+        if let Ok(guard) = self.workspaces.lock() {
+            for (root, search) in guard.iter() {
+                if paths.iter().any(|path| path.starts_with(root)) {
+                    search.stale.store(true, Ordering::Release);
+                }
+            }
+        }
+    }
+
+    fn workspace(&self, root: &Path) -> Result<Arc<WorkspaceSearch>, String> {
+        let mut guard = self
+            .workspaces
+            .lock()
+            .map_err(|_| "search index lock poisoned".to_string())?;
+        Ok(guard.entry(root.to_path_buf()).or_default().clone())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Commands
+// ---------------------------------------------------------------------------
+
+/// Full-text search over the skills in a workspace. See the module docs for
+/// the query syntax. `limit` defaults to 50.
+#[tauri::command]
+pub async fn search_skills(
+    search: State<'_, SearchManager>,
+    workspaces: State<'_, WorkspaceRegistry>,
+    workspace_root: String,
+    query: String,
+    limit: Option<usize>,
+) -> Result<SearchResponse, String> {
+    let root = workspaces.resolve(&workspace_root)?;
+    let workspace = search.workspace(&root)?;
+    let mut guard = workspace
+        .state
+        .lock()
+        .map_err(|_| "search index lock poisoned".to_string())?;
+    let entry = guard.get_or_insert_with(|| WorkspaceIndex {
+        index: SearchIndex::default(),
+        refreshed_at: None,
+    });
+
+    // Cleared before refreshing, so events during the refresh are kept
+    let changed = workspace.stale.swap(false, Ordering::AcqRel);
+    let due = changed
+        || entry
+            .refreshed_at
+            .is_none_or(|at| at.elapsed() >= SEARCH_REFRESH_INTERVAL);
+    if due {
+        if let Err(error) = entry.index.refresh(&root) {
+            workspace.stale.store(true, Ordering::Release);
+            return Err(error);
+        }
+        entry.refreshed_at = Some(Instant::now());
+    }
+
+    let limit = limit.unwrap_or(DEFAULT_SEARCH_LIMIT).min(MAX_SEARCH_LIMIT);
+    Ok(entry.index.search(&root, &query, limit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index_of(files: &[(&str, &str)]) -> SearchIndex {
+        let mut index = SearchIndex::default();
+        for (path, content) in files {
+            let doc = IndexedDoc::from_content(Path::new(path), content, 0, 0).unwrap();
+            index.insert(doc);
+        }
+        index
+    }
+
+    fn paths(response: &SearchResponse) -> Vec<&str> {
+        response
+            .hits
+            .iter()
+            .map(|hit| hit.relative_path.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_search_ranks_and_filters() {
+        let mut index = index_of(&[
+            (
+                "/ws/pandas.md",
+                "---\nname: py-pandas\ntype: skill\ntags: [pandas, data]\n---\nLoad a data frame with pandas.",
+            ),
+            (
+                "/ws/polars.md",
+                "---\nname: polars\ntype: skill\nstatus: draft\n---\nA faster data frame library than pandas.",
+            ),
+            (
+                "/ws/agent.md",
+                "---\nname: analyst\ntype: subagent\n---\nThe frame of the data is irrelevant.",
+            ),
+        ]);
+        let root = Path::new("/ws");
+
+        let hits = index.search(root, "pandas", 10);
+        assert_eq!(paths(&hits), vec!["pandas.md", "polars.md"]);
+
+        let phrase = index.search(root, "\"data frame\"", 10);
+        assert_eq!(phrase.total, 2);
+        assert!(!paths(&phrase).contains(&"agent.md"));
+
+        assert_eq!(paths(&index.search(root, "pol*", 10)), vec!["polars.md"]);
+        assert_eq!(
+            paths(&index.search(root, "tag:pandas", 10)),
+            vec!["pandas.md"]
+        );
+        assert_eq!(
+            paths(&index.search(root, "data type:skill -status:draft", 10)),
+            vec!["pandas.md"]
+        );
+        assert_eq!(
+            paths(&index.search(root, "frame -pandas", 10)),
+            vec!["agent.md"]
+        );
+
+        index.remove("/ws/pandas.md");
+        assert_eq!(paths(&index.search(root, "pandas", 10)), vec!["polars.md"]);
+        assert!(!index.terms.contains_key("py"));
+    }
+
+    #[test]
+    fn test_snippet_highlights_matches() {
+        let terms: HashSet<String> = ["pandas".to_string()].into_iter().collect();
+        let parts = snippet_parts("Use Pandas\nto load data.", &terms).unwrap();
+        assert_eq!(
+            parts,
+            vec![
+                SnippetPart {
+                    text: "Use ".to_string(),
+                    highlight: false
+                },
+                SnippetPart {
+                    text: "Pandas".to_string(),
+                    highlight: true
+                },
+                SnippetPart {
+                    text: " to load data.".to_string(),
+                    highlight: false
+                },
+            ]
+        );
+        assert!(snippet_parts("nothing here", &terms).is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_mark_changed_follows_symlinked_paths() {
+        let base = crate::test_support::TempDir::new("search-watch");
+        std::fs::create_dir_all(base.join("vault")).unwrap();
+        std::os::unix::fs::symlink(base.join("vault"), base.join("link")).unwrap();
+        let root = base.join("vault").canonicalize().unwrap();
+
+        let manager = SearchManager::default();
+        let workspace = manager.workspace(&root).unwrap();
+        // A removed file under the link still maps into the workspace
+        manager.mark_changed(&[base.join("link/gone.md")]);
+        assert!(workspace.stale.load(Ordering::Acquire));
+    }
+}
@@ -0,0 +1,200 @@
+//! skill_bundle.rs — Directory-bundle skills (`SKILL.md` + resources).
+//!
+//! A directory containing a `SKILL.md` is one skill: the manifest carries the
+//! frontmatter, and every other file below that directory (`scripts/`,
+//! `references/`, assets, ...) is a resource of the bundle rather than a
+//! skill of its own. Nested bundles own their own subtree.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+pub const BUNDLE_MANIFEST: &str = "SKILL.md";
+
+// ---------------------------------------------------------------------------
+// Public types
+// ---------------------------------------------------------------------------
+
+/// Role of a bundle file, from the top-level folder it lives in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BundleResourceKind {
+    /// `scripts/` or `bin/`
+    Script,
+    /// `references/` or `docs/`
+    Reference,
+    /// `assets/` or `templates/`
+    Asset,
+    Other,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleResource {
+    /// Path relative to the bundle root, `/`-separated (e.g. "scripts/run.py").
+    pub path: String,
+    pub kind: BundleResourceKind,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkillBundle {
+    /// Absolute path of the bundle directory.
+    pub root: String,
+    /// Resource files in path order.
+    pub resources: Vec<BundleResource>,
+}
+
+impl SkillBundle {
+    /// Resource paths of kind `Script`, relative to the bundle root.
+    pub fn script_paths(&self) -> impl Iterator<Item = &str> {
+        self.resources
+            .iter()
+            .filter(|resource| resource.kind == BundleResourceKind::Script)
+            .map(|resource| resource.path.as_str())
+    }
+}
+
+/// Bundle roots found in a set of walked files and the resources they own.
+#[derive(Debug, Default)]
+pub struct BundleLayout {
+    resources: BTreeMap<PathBuf, Vec<BundleResource>>,
+}
+
+// ---------------------------------------------------------------------------
+// Detection
+// ---------------------------------------------------------------------------
+
+pub fn is_bundle_manifest(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.eq_ignore_ascii_case(BUNDLE_MANIFEST))
+}
+
+/// Name of the directory holding a bundle manifest, used as the skill name
+/// when the frontmatter has none.
+pub fn bundle_dir_name(manifest: &Path) -> Option<&str> {
+    manifest
+        .parent()
+        .and_then(|dir| dir.file_name())
+        .and_then(|name| name.to_str())
+}
+
+impl BundleLayout {
+    /// Group `files` (already filtered by the scan's ignore rules) into
+    /// bundles. Files outside any bundle are not recorded.
+    pub fn from_files(files: &[PathBuf]) -> Self {
+        let roots: BTreeSet<PathBuf> = files
+            .iter()
+            .filter(|file| is_bundle_manifest(file))
+            .filter_map(|file| file.parent().map(Path::to_path_buf))
+            .collect();
+
+        let mut resources: BTreeMap<PathBuf, Vec<BundleResource>> = roots
+            .iter()
+            .map(|root| (root.clone(), Vec::new()))
+            .collect();
+
+        for file in files {
+            if is_bundle_manifest(file) {
+                continue;
+            }
+            let Some(root) = file.ancestors().skip(1).find(|dir| roots.contains(*dir)) else {
+                continue;
+            };
+            let Ok(relative) = file.strip_prefix(root) else {
+                continue;
+            };
+            if let Some(list) = resources.get_mut(root) {
+                list.push(BundleResource {
+                    path: relative.to_string_lossy().replace('\\', "/"),
+                    kind: resource_kind(relative),
+                });
+            }
+        }
+
+        for list in resources.values_mut() {
+            list.sort_by(|a, b| a.path.cmp(&b.path));
+        }
+
+        Self { resources }
+    }
+
+    /// True when `file` is a resource inside some bundle (not a manifest).
+    pub fn owns(&self, file: &Path) -> bool {
+        !is_bundle_manifest(file)
+            && file
+                .ancestors()
+                .skip(1)
+                .any(|dir| self.resources.contains_key(dir))
+    }
+
+    /// The bundle described by `manifest`, if it is a bundle manifest.
+    pub fn bundle_for(&self, manifest: &Path) -> Option<SkillBundle> {
+        if !is_bundle_manifest(manifest) {
+            return None;
+        }
+        let root = manifest.parent()?;
+        Some(SkillBundle {
+            root: root.to_string_lossy().to_string(),
+            resources: self.resources.get(root).cloned().unwrap_or_default(),
+        })
+    }
+}
+
+fn resource_kind(relative: &Path) -> BundleResourceKind {
+    let mut components = relative.components();
+    let top = components.next().and_then(|c| c.as_os_str().to_str());
+    // A file directly in the bundle root has no folder to classify it by.
+    if components.next().is_none() {
+        return BundleResourceKind::Other;
+    }
+
+    match top {
+        Some("scripts") | Some("bin") => BundleResourceKind::Script,
+        Some("references") | Some("docs") => BundleResourceKind::Reference,
+        Some("assets") | Some("templates") => BundleResourceKind::Asset,
+        _ => BundleResourceKind::Other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout_assigns_resources_to_nearest_bundle() {
+        let files: Vec<PathBuf> = [
+            "/v/pdf/SKILL.md",
+            "/v/pdf/scripts/fill.py",
+            "/v/pdf/references/forms.md",
+            "/v/pdf/LICENSE",
+            "/v/pdf/inner/SKILL.md",
+            "/v/pdf/inner/assets/logo.png",
+            "/v/loose.md",
+        ]
+        .iter()
+        .map(PathBuf::from)
+        .collect();
+
+        let layout = BundleLayout::from_files(&files);
+        let outer = layout.bundle_for(Path::new("/v/pdf/SKILL.md")).unwrap();
+        let paths: Vec<_> = outer.resources.iter().map(|r| r.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec!["LICENSE", "references/forms.md", "scripts/fill.py"]
+        );
+        assert_eq!(
+            outer.script_paths().collect::<Vec<_>>(),
+            vec!["scripts/fill.py"]
+        );
+
+        let inner = layout
+            .bundle_for(Path::new("/v/pdf/inner/SKILL.md"))
+            .unwrap();
+        assert_eq!(inner.resources[0].kind, BundleResourceKind::Asset);
+
+        assert!(layout.owns(Path::new("/v/pdf/references/forms.md")));
+        assert!(!layout.owns(Path::new("/v/pdf/inner/SKILL.md")));
+        assert!(!layout.owns(Path::new("/v/loose.md")));
+        assert!(layout.bundle_for(Path::new("/v/loose.md")).is_none());
+    }
+}
@@ -0,0 +1,670 @@
+//! skill_document.rs — Structured view of a skill's markdown body.
+//!
+//! A small line-based parser that pulls out what the app needs from a body
+//! without a full CommonMark implementation: the heading outline,
+//! `[[wiki-links]]`, fenced code blocks, task list items and relative file
+//! links. Every element carries a byte span into the body (add
+//! `body_offset` for a position in the whole file), so graph building,
+//! linting and refactors can point at or rewrite the exact source text.
+//!
+//! Links and headings inside fenced code blocks and inline code spans are
+//! ignored.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// ---------------------------------------------------------------------------
+// Public types
+// ---------------------------------------------------------------------------
+
+/// Byte range into the body, with the 1-based line it starts on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Heading {
+    pub level: u8,
+    pub text: String,
+    /// GitHub-style anchor slug, unique within the document.
+    pub anchor: String,
+    pub span: Span,
+}
+
+/// `[[target#anchor|alias]]`, or `![[target]]` when `embed` is set.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WikiLink {
+    pub target: String,
+    pub anchor: Option<String>,
+    pub alias: Option<String>,
+    pub embed: bool,
+    pub span: Span,
+    /// The target as written (before trimming), for rewriting it in place.
+    pub target_span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CodeBlock {
+    /// First word of the info string, e.g. `python` for ```` ```python ````.
+    pub lang: Option<String>,
+    /// Fences included.
+    pub span: Span,
+    /// Just the code between the fences.
+    pub content_span: Span,
+    /// False when the body ends before the closing fence.
+    pub closed: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskItem {
+    pub checked: bool,
+    pub text: String,
+    /// Leading whitespace before the list marker, in bytes.
+    pub indent: usize,
+    pub span: Span,
+    /// The `[ ]` / `[x]` box, for toggling it in place.
+    pub checkbox_span: Span,
+}
+
+/// `[text](path)` or `![alt](path)` pointing at a relative file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileLink {
+    pub text: String,
+    pub target: String,
+    pub anchor: Option<String>,
+    pub image: bool,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SkillDocument {
+    /// Byte offset of the body within the file (the frontmatter length).
+    pub body_offset: usize,
+    pub headings: Vec<Heading>,
+    pub wiki_links: Vec<WikiLink>,
+    pub code_blocks: Vec<CodeBlock>,
+    pub tasks: Vec<TaskItem>,
+    pub file_links: Vec<FileLink>,
+}
+
+// ---------------------------------------------------------------------------
+// Block structure
+// ---------------------------------------------------------------------------
+
+struct OpenFence {
+    marker: char,
+    len: usize,
+    lang: Option<String>,
+    start: usize,
+    line: usize,
+    content_start: usize,
+}
+
+/// Lines of the current paragraph, for setext (`===` / `---`) headings.
+struct Paragraph {
+    start: usize,
+    line: usize,
+    text: String,
+}
+
+/// Up to three spaces of indentation, as CommonMark allows before blocks.
+fn strip_block_indent(line: &str) -> Option<&str> {
+    let rest = line.trim_start_matches(' ');
+    (line.len() - rest.len() <= 3).then_some(rest)
+}
+
+/// `(marker, length, info string)` for an opening code fence.
+fn opening_fence(line: &str) -> Option<(char, usize, &str)> {
+    let rest = strip_block_indent(line)?;
+    let marker = rest.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let len = rest.len() - rest.trim_start_matches(marker).len();
+    let info = rest[len..].trim();
+    if len < 3 || (marker == '`' && info.contains('`')) {
+        return None;
+    }
+    Some((marker, len, info))
+}
+
+fn closes_fence(line: &str, open: &OpenFence) -> bool {
+    let Some(rest) = strip_block_indent(line) else {
+        return false;
+    };
+    let run = rest.len() - rest.trim_start_matches(open.marker).len();
+    run >= open.len && rest[run..].trim().is_empty()
+}
+
+/// `(level, text)` for an ATX heading, closing `#`s removed.
+fn atx_heading(line: &str) -> Option<(u8, &str)> {
+    let rest = strip_block_indent(line)?;
+    let level = rest.len() - rest.trim_start_matches('#').len();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    let after = &rest[level..];
+    if !(after.is_empty() || after.starts_with([' ', '\t'])) {
+        return None;
+    }
+
+    let text = after.trim();
+    let without_closing = text.trim_end_matches('#');
+    let text = if without_closing.is_empty() {
+        ""
+    } else if without_closing.ends_with([' ', '\t']) {
+        without_closing.trim_end()
+    } else {
+        text
+    };
+    Some((level as u8, text))
+}
+
+fn setext_level(line: &str) -> Option<u8> {
+    let rest = strip_block_indent(line)?.trim_end();
+    let marker = rest.chars().next()?;
+    let level = match marker {
+        '=' => 1,
+        '-' => 2,
+        _ => return None,
+    };
+    rest.chars().all(|c| c == marker).then_some(level)
+}
+
+/// Byte length of a list marker plus the whitespace after it.
+fn list_marker_len(rest: &str) -> Option<usize> {
+    let bytes = rest.as_bytes();
+    let marker = match bytes.first()? {
+        b'-' | b'*' | b'+' => 1,
+        b'0'..=b'9' => {
+            let digits = bytes.iter().take_while(|b| b.is_ascii_digit()).count();
+            if digits > 9 || !matches!(bytes.get(digits), Some(b'.' | b')')) {
+                return None;
+            }
+            digits + 1
+        }
+        _ => return None,
+    };
+    let spaces = rest[marker..].len() - rest[marker..].trim_start_matches([' ', '\t']).len();
+    (spaces > 0 || marker == rest.len()).then_some(marker + spaces)
+}
+
+/// GitHub-style anchor: lowercase, spaces to `-`, other punctuation dropped.
+fn slugify(text: &str) -> String {
+    text.trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// Inline scanning
+// ---------------------------------------------------------------------------
+
+/// Absolute and "scheme:" URLs, fragment-only links and rooted paths are not
+/// links to files next to the skill.
+fn is_relative_target(target: &str) -> bool {
+    if target.is_empty() || target.starts_with(['#', '/', '\\']) {
+        return false;
+    }
+    match target.find(':') {
+        Some(colon) => target[..colon].contains('/'),
+        None => true,
+    }
+}
+
+fn split_anchor(target: &str) -> (&str, Option<String>) {
+    match target.split_once('#') {
+        Some((path, anchor)) => (path, Some(anchor.to_string())),
+        None => (target, None),
+    }
+}
+
+/// Index just past the `]` matching the `[` at `open`, skipping escapes.
+fn closing_bracket(line: &str, open: usize) -> Option<usize> {
+    let bytes = line.as_bytes();
+    let mut depth = 0usize;
+    let mut i = open;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b'[' => depth += 1,
+            b']' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i + 1);
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Destination of an inline link starting at the `(` at `open`, and the
+/// index just past the closing `)`.
+fn link_destination(line: &str, open: usize) -> Option<(&str, usize)> {
+    let rest = &line[open + 1..];
+    let leading = rest.len() - rest.trim_start().len();
+    let dest_start = open + 1 + leading;
+    let bytes = line.as_bytes();
+
+    let (dest, mut i) = if bytes.get(dest_start) == Some(&b'<') {
+        let close = line[dest_start..].find('>')? + dest_start;
+        (&line[dest_start + 1..close], close + 1)
+    } else {
+        let mut depth = 0usize;
+        let mut i = dest_start;
+        while i < bytes.len() {
+            match bytes[i] {
+                b'(' => depth += 1,
+                b')' if depth == 0 => break,
+                b')' => depth -= 1,
+                b' ' | b'\t' => break,
+                _ => {}
+            }
+            i += 1;
+        }
+        (&line[dest_start..i], i)
+    };
+
+    // Optional title, then the closing parenthesis.
+    let mut quote = None;
+    while i < bytes.len() {
+        match (bytes[i], quote) {
+            (b'"' | b'\'', None) => quote = Some(bytes[i]),
+            (q, Some(open_quote)) if q == open_quote => quote = None,
+            (b')', None) => return Some((dest, i + 1)),
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+struct LineScanner<'a> {
+    line: &'a str,
+    base: usize,
+    line_no: usize,
+}
+
+impl LineScanner<'_> {
+    fn span(&self, start: usize, end: usize) -> Span {
+        Span {
+            start: self.base + start,
+            end: self.base + end,
+            line: self.line_no,
+        }
+    }
+
+    /// The wiki-link whose `[[` is at `open`, and the index past its `]]`.
+    fn wiki_link(&self, open: usize, embed: bool) -> Option<(WikiLink, usize)> {
+        let inner_start = open + 2;
+        let inner_len = self.line[inner_start..].find("]]")?;
+        let inner = &self.line[inner_start..inner_start + inner_len];
+        if inner.contains('[') {
+            return None;
+        }
+
+        let target_len = inner.find(['|', '#']).unwrap_or(inner.len());
+        let target = inner[..target_len].trim();
+        let rest = &inner[target_len..];
+        let (anchor, alias) = match rest.split_once('|') {
+            Some((anchor, alias)) => (anchor, Some(alias.trim().to_string())),
+            None => (rest, None),
+        };
+        let anchor = anchor
+            .strip_prefix('#')
+            .map(|a| a.trim().to_string())
+            .filter(|a| !a.is_empty());
+        if target.is_empty() && anchor.is_none() {
+            return None;
+        }
+
+        let end = inner_start + inner_len + 2;
+        let start = if embed { open - 1 } else { open };
+        Some((
+            WikiLink {
+                target: target.to_string(),
+                anchor,
+                alias: alias.filter(|a| !a.is_empty()),
+                embed,
+                span: self.span(start, end),
+                target_span: self.span(inner_start, inner_start + target_len),
+            },
+            end,
+        ))
+    }
+
+    /// The `[text](dest)` whose `[` is at `open`, and the index past it.
+    fn file_link(&self, open: usize, image: bool) -> Option<(Option<FileLink>, usize)> {
+        let text_end = closing_bracket(self.line, open)?;
+        if self.line.as_bytes().get(text_end) != Some(&b'(') {
+            return None;
+        }
+        let (dest, end) = link_destination(self.line, text_end)?;
+
+        let link = is_relative_target(dest).then(|| {
+            let (target, anchor) = split_anchor(dest);
+            let start = if image { open - 1 } else { open };
+            FileLink {
+                text: self.line[open + 1..text_end - 1].to_string(),
+                target: target.to_string(),
+                anchor,
+                image,
+                span: self.span(start, end),
+            }
+        });
+        Some((link, end))
+    }
+
+    fn scan(&self, doc: &mut SkillDocument) {
+        let bytes = self.line.as_bytes();
+        let mut i = 0;
+        while i < bytes.len() {
+            let after_bang = i > 0 && bytes[i - 1] == b'!';
+            match bytes[i] {
+                b'\\' => i += 2,
+                b'`' => {
+                    // Inline code: skip to the closing run of equal length.
+                    let run = self.line[i..].len() - self.line[i..].trim_start_matches('`').len();
+                    let fence = &self.line[i..i + run];
+                    let mut search = i + run;
+                    i = loop {
+                        match self.line[search..].find(fence) {
+                            Some(found) => {
+                                let at = search + found;
+                                let len = self.line[at..].len()
+                                    - self.line[at..].trim_start_matches('`').len();
+                                if len == run {
+                                    break at + run;
+                                }
+                                search = at + len;
+                            }
+                            None => break i + run,
+                        }
+                    };
+                }
+                b'[' if bytes.get(i + 1) == Some(&b'[') => match self.wiki_link(i, after_bang) {
+                    Some((link, end)) => {
+                        doc.wiki_links.push(link);
+                        i = end;
+                    }
+                    None => i += 2,
+                },
+                b'[' => match self.file_link(i, after_bang) {
+                    Some((link, end)) => {
+                        doc.file_links.extend(link);
+                        i = end;
+                    }
+                    None => i += 1,
+                },
+                _ => i += 1,
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Parsing
+// ---------------------------------------------------------------------------
+
+/// Task item on `line`, if it is a `- [ ]` / `- [x]` list item.
+fn task_item(line: &str, base: usize, line_no: usize) -> Option<TaskItem> {
+    let rest = line.trim_start_matches([' ', '\t']);
+    let indent = line.len() - rest.len();
+    let marker = list_marker_len(rest)?;
+    let after = &rest[marker..];
+    let checked = match after.get(..3)? {
+        "[ ]" => false,
+        "[x]" | "[X]" => true,
+        _ => return None,
+    };
+    if !(after.len() == 3 || after[3..].starts_with([' ', '\t'])) {
+        return None;
+    }
+
+    let box_start = base + indent + marker;
+    let span = |start: usize, end: usize| Span {
+        start,
+        end,
+        line: line_no,
+    };
+    Some(TaskItem {
+        checked,
+        text: after[3..].trim().to_string(),
+        indent,
+        span: span(base, base + line.len()),
+        checkbox_span: span(box_start, box_start + 3),
+    })
+}
+
+/// Parse a skill body (frontmatter already removed).
+pub fn parse_document(body: &str) -> SkillDocument {
+    let mut doc = SkillDocument::default();
+    let mut anchors: HashMap<String, usize> = HashMap::new();
+    let mut push_heading = |doc: &mut SkillDocument, level: u8, text: &str, span: Span| {
+        let base = slugify(text);
+        let seen = anchors.entry(base.clone()).or_insert(0);
+        let anchor = if *seen == 0 {
+            base
+        } else {
+            format!("{}-{}", base, seen)
+        };
+        *seen += 1;
+        doc.headings.push(Heading {
+            level,
+            text: text.to_string(),
+            anchor,
+            span,
+        });
+    };
+
+    let mut fence: Option<OpenFence> = None;
+    let mut paragraph: Option<Paragraph> = None;
+    let mut offset = 0;
+
+    for (index, raw) in body.split_inclusive('\n').enumerate() {
+        let line_no = index + 1;
+        let line = raw.trim_end_matches(['\n', '\r']);
+        let start = offset;
+        offset += raw.len();
+        let line_end = start + line.len();
+
+        if let Some(open) = &fence {
+            if closes_fence(line, open) {
+                doc.code_blocks.push(CodeBlock {
+                    lang: open.lang.clone(),
+                    span: Span {
+                        start: open.start,
+                        end: line_end,
+                        line: open.line,
+                    },
+                    content_span: Span {
+                        start: open.content_start,
+                        end: start,
+                        line: open.line + 1,
+                    },
+                    closed: true,
+                });
+                fence = None;
+            }
+            continue;
+        }
+
+        if let Some((marker, len, info)) = opening_fence(line) {
+            fence = Some(OpenFence {
+                marker,
+                len,
+                lang: info.split_whitespace().next().map(String::from),
+                start,
+                line: line_no,
+                content_start: offset,
+            });
+            paragraph = None;
+            continue;
+        }
+
+        if line.trim().is_empty() {
+            paragraph = None;
+            continue;
+        }
+
+        if let Some(level) = setext_level(line) {
+            // Without a paragraph above, `---` is a thematic break.
+            if let Some(para) = paragraph.take() {
+                let span = Span {
+                    start: para.start,
+                    end: line_end,
+                    line: para.line,
+                };
+                push_heading(&mut doc, level, &para.text, span);
+                continue;
+            } else if level == 2 {
+                continue;
+            }
+        }
+
+        LineScanner {
+            line,
+            base: start,
+            line_no,
+        }
+        .scan(&mut doc);
+
+        if let Some((level, text)) = atx_heading(line) {
+            let span = Span {
+                start,
+                end: line_end,
+                line: line_no,
+            };
+            push_heading(&mut doc, level, text, span);
+            paragraph = None;
+            continue;
+        }
+
+        if let Some(task) = task_item(line, start, line_no) {
+            doc.tasks.push(task);
+        }
+
+        let rest = line.trim_start();
+        let starts_block = list_marker_len(rest).is_some() || rest.starts_with('>');
+        paragraph = match paragraph.take() {
+            Some(mut para) if !starts_block => {
+                para.text.push(' ');
+                para.text.push_str(line.trim());
+                Some(para)
+            }
+            _ if starts_block => None,
+            _ => Some(Paragraph {
+                start,
+                line: line_no,
+                text: line.trim().to_string(),
+            }),
+        };
+    }
+
+    if let Some(open) = fence {
+        doc.code_blocks.push(CodeBlock {
+            lang: open.lang,
+            span: Span {
+                start: open.start,
+                end: body.len(),
+                line: open.line,
+            },
+            content_span: Span {
+                start: open.content_start.min(body.len()),
+                end: body.len(),
+                line: open.line + 1,
+            },
+            closed: false,
+        });
+    }
+
+    doc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_document_blocks() {
+        let body = "# Intro\n\nSee [[py-pandas#load|Pandas]] and ![[diagram]].\n\n```python\nx = '[[not-a-link]]'\n```\n\nSetup\n=====\n\n- [ ] install\n  - [x] `[[skipped]]` done\n\n## Intro\n";
+        let doc = parse_document(body);
+
+        let outline: Vec<(u8, &str, &str)> = doc
+            .headings
+            .iter()
+            .map(|h| (h.level, h.text.as_str(), h.anchor.as_str()))
+            .collect();
+        assert_eq!(
+            outline,
+            vec![
+                (1, "Intro", "intro"),
+                (1, "Setup", "setup"),
+                (2, "Intro", "intro-1")
+            ]
+        );
+
+        assert_eq!(doc.wiki_links.len(), 2);
+        let link = &doc.wiki_links[0];
+        assert_eq!(link.target, "py-pandas");
+        assert_eq!(link.anchor.as_deref(), Some("load"));
+        assert_eq!(link.alias.as_deref(), Some("Pandas"));
+        assert_eq!(
+            &body[link.span.start..link.span.end],
+            "[[py-pandas#load|Pandas]]"
+        );
+        assert_eq!(
+            &body[link.target_span.start..link.target_span.end],
+            "py-pandas"
+        );
+        assert_eq!(link.span.line, 3);
+        assert!(doc.wiki_links[1].embed);
+
+        let code = &doc.code_blocks[0];
+        assert_eq!(code.lang.as_deref(), Some("python"));
+        assert_eq!(
+            &body[code.content_span.start..code.content_span.end],
+            "x = '[[not-a-link]]'\n"
+        );
+
+        assert_eq!(doc.tasks.len(), 2);
+        assert!(!doc.tasks[0].checked);
+        assert!(doc.tasks[1].checked);
+        assert_eq!(doc.tasks[1].indent, 2);
+        let checkbox = doc.tasks[1].checkbox_span;
+        assert_eq!(&body[checkbox.start..checkbox.end], "[x]");
+    }
+
+    #[test]
+    fn test_parse_document_file_links() {
+        let body = "Run [the script](scripts/run.py#L3) or see ![plot](./img/a.png \"Plot\").\nNot [web](https://example.com), [top](#intro) or [abs](/etc/passwd).\n";
+        let doc = parse_document(body);
+
+        let links: Vec<(&str, Option<&str>, bool)> = doc
+            .file_links
+            .iter()
+            .map(|l| (l.target.as_str(), l.anchor.as_deref(), l.image))
+            .collect();
+        assert_eq!(
+            links,
+            vec![
+                ("scripts/run.py", Some("L3"), false),
+                ("./img/a.png", None, true)
+            ]
+        );
+        let image = &doc.file_links[1];
+        assert_eq!(
+            &body[image.span.start..image.span.end],
+            "![plot](./img/a.png \"Plot\")"
+        );
+    }
+}
@@ -0,0 +1,605 @@
+//! skill_query.rs — Structured queries over the scanned skill index.
+//!
+//! A query is a list of whitespace-separated clauses, all of which must hold:
+//!
+//!   - `type:subagent` matches a frontmatter value, ignoring case; lists
+//!     match on any item, and `type:skill,hook` matches either value
+//!   - `tag:pandas` is shorthand for the `tags` list
+//!   - `related:py-pandas` compares `related` entries as graph node ids
+//!   - `path:agents/` matches a relative path prefix
+//!   - `has:scripts` needs the key present and non-empty (`has:bundle` for
+//!     directory bundles)
+//!   - `updated:<30d` means last committed within 30 days (`h`, `d`, `w`,
+//!     `y`); `updated:>2024-01-01` compares against a date, and date-valued
+//!     frontmatter such as `created` works the same way
+//!   - `version:>=2` compares numbers
+//!   - `-category:legacy` negates any clause
+//!   - a bare word matches when the name or path contains it
+//!   - `sort:-updated` sorts, `-` for descending; repeat for tie-breakers
+//!
+//! Results are stable: ties, and the default order, fall back to the path.
+//!
+//! Tauri commands exposed:
+//!   - `query_skills` → matching `SkillIndex` entries, sorted
+
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{State, Window};
+
+use crate::autogit::find_git_root;
+use crate::fs_scan::{index_workspace, ScanManager, SkillIndex};
+use crate::git_reader::last_commit_times;
+use crate::graph_builder::normalize_id;
+use crate::scan_cache::mtime_millis;
+use crate::search_index::split_query;
+use crate::workspace::WorkspaceRegistry;
+
+/// Key filled from git history rather than frontmatter.
+const UPDATED_KEY: &str = "updated";
+
+// ---------------------------------------------------------------------------
+// Public types
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryMatch {
+    #[serde(flatten)]
+    pub skill: SkillIndex,
+    /// Path relative to the workspace root, `/`-separated.
+    pub relative_path: String,
+    /// Unix time of the last commit touching the file (its mtime when never
+    /// committed). Only filled in when the query filters or sorts on it.
+    pub updated: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryResult {
+    pub skills: Vec<QueryMatch>,
+    /// Number of matching skills before `limit` was applied.
+    pub total: usize,
+}
+
+// ---------------------------------------------------------------------------
+// Parsing
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CompareOp {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl CompareOp {
+    fn holds(self, ordering: Ordering) -> bool {
+        match self {
+            CompareOp::Lt => ordering.is_lt(),
+            CompareOp::Le => ordering.is_le(),
+            CompareOp::Gt => ordering.is_gt(),
+            CompareOp::Ge => ordering.is_ge(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Operand {
+    /// Seconds before now; compared against the age of a timestamp.
+    Age(i64),
+    /// Unix time of a calendar date (midnight UTC).
+    Date(i64),
+    Number(f64),
+    Text(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Predicate {
+    Contains(String),
+    Equals {
+        key: String,
+        values: Vec<String>,
+    },
+    Compare {
+        key: String,
+        op: CompareOp,
+        operand: Operand,
+    },
+    Has(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Clause {
+    predicate: Predicate,
+    negated: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct SortKey {
+    key: String,
+    descending: bool,
+}
+
+#[derive(Debug, Default)]
+struct SkillQuery {
+    clauses: Vec<Clause>,
+    sort: Vec<SortKey>,
+}
+
+impl SkillQuery {
+    fn uses_updated(&self) -> bool {
+        let in_clauses = self.clauses.iter().any(|clause| match &clause.predicate {
+            Predicate::Equals { key, .. }
+            | Predicate::Compare { key, .. }
+            | Predicate::Has(key) => key == UPDATED_KEY,
+            Predicate::Contains(_) => false,
+        });
+        in_clauses || self.sort.iter().any(|sort| sort.key == UPDATED_KEY)
+    }
+}
+
+fn canonical_key(key: &str) -> String {
+    match key {
+        "tag" => "tags".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// Days since 1970-01-01 for a proleptic Gregorian date (Howard Hinnant's
+/// `days_from_civil`).
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let month = i64::from(month);
+    let doy = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Unix time of the `YYYY-MM-DD` date at the start of `text`, so full
+/// datetimes compare by their day.
+fn parse_date(text: &str) -> Option<i64> {
+    let date = text.trim().get(..10)?;
+    let mut parts = date.split('-');
+    let year: i64 = parts.next()?.parse().ok()?;
+    let month: u32 = parts.next()?.parse().ok()?;
+    let day: u32 = parts.next()?.parse().ok()?;
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    Some(days_from_civil(year, month, day) * 86_400)
+}
+
+/// An age like `30d` as the amount and the seconds in its unit.
+fn split_age(text: &str) -> Option<(u64, u64)> {
+    let unit = text.chars().last()?;
+    let seconds = match unit {
+        'h' => 3_600,
+        'd' => 86_400,
+        'w' => 7 * 86_400,
+        'y' => 365 * 86_400,
+        _ => return None,
+    };
+    let amount: u64 = text[..text.len() - 1].parse().ok()?;
+    Some((amount, seconds))
+}
+
+fn parse_operand(text: &str) -> Result<Operand, String> {
+    if let Some((amount, seconds)) = split_age(text) {
+        amount
+            .checked_mul(seconds)
+            .and_then(|age| i64::try_from(age).ok())
+            .map(Operand::Age)
+            .ok_or_else(|| format!("Age out of range: {}", text))
+    } else if let Some(date) = parse_date(text) {
+        Ok(Operand::Date(date))
+    } else if let Ok(number) = text.parse::<f64>() {
+        Ok(Operand::Number(number))
+    } else {
+        Ok(Operand::Text(text.to_lowercase()))
+    }
+}
+
+fn parse_query(query: &str) -> Result<SkillQuery, String> {
+    let mut parsed = SkillQuery::default();
+
+    for raw in split_query(query) {
+        let Some(key) = raw.key else {
+            if !raw.value.is_empty() {
+                parsed.clauses.push(Clause {
+                    predicate: Predicate::Contains(raw.value.to_lowercase()),
+                    negated: raw.negated,
+                });
+            }
+            continue;
+        };
+
+        if raw.value.is_empty() {
+            return Err(format!("Missing value for {}:", key));
+        }
+
+        let predicate = match key.as_str() {
+            "sort" => {
+                let (descending, sort_key) = match raw.value.strip_prefix('-') {
+                    Some(rest) => (true, rest),
+                    None => (false, raw.value.as_str()),
+                };
+                parsed.sort.push(SortKey {
+                    key: canonical_key(&sort_key.to_lowercase()),
+                    descending,
+                });
+                continue;
+            }
+            "has" => Predicate::Has(canonical_key(&raw.value.to_lowercase())),
+            _ => {
+                let key = canonical_key(&key);
+                let comparison = [
+                    (">=", CompareOp::Ge),
+                    ("<=", CompareOp::Le),
+                    (">", CompareOp::Gt),
+                    ("<", CompareOp::Lt),
+                ]
+                .into_iter()
+                .find_map(|(symbol, op)| Some((op, raw.value.strip_prefix(symbol)?)))
+                .filter(|_| !raw.quoted);
+
+                match comparison {
+                    Some((_, "")) => {
+                        return Err(format!("Missing value to compare in {}:{}", key, raw.value))
+                    }
+                    Some((op, operand)) => Predicate::Compare {
+                        key,
+                        op,
+                        operand: parse_operand(operand)?,
+                    },
+                    None => Predicate::Equals {
+                        key,
+                        values: raw
+                            .value
+                            .split(',')
+                            .map(|v| v.trim().to_string())
+                            .filter(|v| !v.is_empty())
+                            .collect(),
+                    },
+                }
+            }
+        };
+
+        parsed.clauses.push(Clause {
+            predicate,
+            negated: raw.negated,
+        });
+    }
+
+    Ok(parsed)
+}
+
+// ---------------------------------------------------------------------------
+// Evaluation
+// ---------------------------------------------------------------------------
+
+struct Row {
+    skill: SkillIndex,
+    relative_path: String,
+    updated: Option<u64>,
+}
+
+impl Row {
+    /// Node type as the graph shows it: `moc: true` wins over `type`.
+    fn node_type(&self) -> String {
+        let fm = self.skill.frontmatter.as_ref();
+        if fm.and_then(|fm| fm.get("moc")).and_then(|v| v.as_bool()) == Some(true) {
+            return "moc".to_string();
+        }
+        fm.and_then(|fm| fm.get("type"))
+            .and_then(|v| v.as_str())
+            .unwrap_or("skill")
+            .to_string()
+    }
+
+    /// Values of `key` for this skill; list-valued keys give one per item.
+    fn values(&self, key: &str) -> Vec<serde_json::Value> {
+        use serde_json::Value;
+        match key {
+            "name" => vec![Value::from(self.skill.name.as_str())],
+            "path" => vec![Value::from(self.relative_path.as_str())],
+            "type" => vec![Value::from(self.node_type())],
+            UPDATED_KEY => self.updated.map(Value::from).into_iter().collect(),
+            _ => match self.skill.frontmatter.as_ref().and_then(|fm| fm.get(key)) {
+                Some(Value::Array(items)) => items.clone(),
+                Some(Value::Null) | None => Vec::new(),
+                Some(value) => vec![value.clone()],
+            },
+        }
+    }
+}
+
+fn value_text(value: &serde_json::Value) -> Option<String> {
+    match value {
+        serde_json::Value::String(s) => Some(s.clone()),
+        serde_json::Value::Number(_) | serde_json::Value::Bool(_) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+fn value_number(value: &serde_json::Value) -> Option<f64> {
+    value
+        .as_f64()
+        .or_else(|| value.as_str().and_then(|s| s.trim().parse().ok()))
+}
+
+/// Timestamps are stored as numbers for `updated` and as date strings in
+/// frontmatter.
+fn value_time(key: &str, value: &serde_json::Value) -> Option<i64> {
+    if key == UPDATED_KEY {
+        value.as_i64()
+    } else {
+        value.as_str().and_then(parse_date)
+    }
+}
+
+fn equals(key: &str, value: &serde_json::Value, wanted: &str) -> bool {
+    let Some(text) = value_text(value) else {
+        return false;
+    };
+    match key {
+        "path" => text.to_lowercase().starts_with(&wanted.to_lowercase()),
+        "related" => normalize_id(&text) == normalize_id(wanted),
+        _ => text.eq_ignore_ascii_case(wanted),
+    }
+}
+
+fn compare(key: &str, value: &serde_json::Value, operand: &Operand, now: i64) -> Option<Ordering> {
+    match operand {
+        Operand::Age(age) => {
+            let elapsed = now.saturating_sub(value_time(key, value)?);
+            Some(elapsed.cmp(age))
+        }
+        Operand::Date(date) => Some(value_time(key, value)?.cmp(date)),
+        Operand::Number(number) => value_number(value)?.partial_cmp(number),
+        Operand::Text(text) => Some(value_text(value)?.to_lowercase().cmp(text)),
+    }
+}
+
+fn matches(row: &Row, predicate: &Predicate, now: i64) -> bool {
+    match predicate {
+        Predicate::Contains(text) => {
+            row.skill.name.to_lowercase().contains(text)
+                || row.relative_path.to_lowercase().contains(text)
+        }
+        Predicate::Equals { key, values } => row
+            .values(key)
+            .iter()
+            .any(|value| values.iter().any(|wanted| equals(key, value, wanted))),
+        Predicate::Compare { key, op, operand } => row.values(key).iter().any(|value| {
+            compare(key, value, operand, now).is_some_and(|ordering| op.holds(ordering))
+        }),
+        Predicate::Has(key) if key == "bundle" => row.skill.bundle.is_some(),
+        Predicate::Has(key) => row
+            .values(key)
+            .iter()
+            .any(|value| value_text(value).is_some_and(|text| !text.trim().is_empty())),
+    }
+}
+
+/// Order two values of a sort key: numbers before text, text ignoring case.
+fn compare_sort_values(a: &serde_json::Value, b: &serde_json::Value) -> Ordering {
+    match (a.as_f64(), b.as_f64()) {
+        (Some(x), Some(y)) => x.total_cmp(&y),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => {
+            let text = |v: &serde_json::Value| value_text(v).unwrap_or_default().to_lowercase();
+            text(a).cmp(&text(b))
+        }
+    }
+}
+
+fn compare_rows(a: &Row, b: &Row, sort: &[SortKey]) -> Ordering {
+    for key in sort {
+        let ordering = match (a.values(&key.key).first(), b.values(&key.key).first()) {
+            // Skills without the key go last in either direction.
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) => return Ordering::Greater,
+            (Some(_), None) => return Ordering::Less,
+            (Some(x), Some(y)) => compare_sort_values(x, y),
+        };
+        let ordering = if key.descending {
+            ordering.reverse()
+        } else {
+            ordering
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    a.skill.path.cmp(&b.skill.path)
+}
+
+/// Filter and sort `skills`. `updated` maps absolute paths to the time used
+/// for the `updated` key.
+fn run_query(
+    query: &SkillQuery,
+    skills: Vec<SkillIndex>,
+    root: &Path,
+    updated: &HashMap<PathBuf, u64>,
+    now: i64,
+) -> Vec<QueryMatch> {
+    let mut rows: Vec<Row> = skills
+        .into_iter()
+        .map(|skill| {
+            let path = Path::new(&skill.path);
+            let relative_path = path
+                .strip_prefix(root)
+                .map(|p| p.to_string_lossy().replace('\\', "/"))
+                .unwrap_or_else(|_| skill.path.clone());
+            let updated = updated.get(path).copied();
+            Row {
+                skill,
+                relative_path,
+                updated,
+            }
+        })
+        .filter(|row| {
+            query
+                .clauses
+                .iter()
+                .all(|clause| matches(row, &clause.predicate, now) != clause.negated)
+        })
+        .collect();
+
+    rows.sort_by(|a, b| compare_rows(a, b, &query.sort));
+    rows.into_iter()
+        .map(|row| QueryMatch {
+            skill: row.skill,
+            relative_path: row.relative_path,
+            updated: row.updated,
+        })
+        .collect()
+}
+
+/// Last commit time per file, falling back to the mtime for files that were
+/// never committed (or when the workspace is not in a repository).
+fn updated_times(root: &Path, skills: &[SkillIndex]) -> HashMap<PathBuf, u64> {
+    let wanted: HashSet<PathBuf> = skills.iter().map(|s| PathBuf::from(&s.path)).collect();
+    let mut times = find_git_root(root)
+        .map(|repo| last_commit_times(&repo, root, &wanted))
+        .unwrap_or_default();
+    for skill in skills {
+        let path = PathBuf::from(&skill.path);
+        if let Entry::Vacant(entry) = times.entry(path) {
+            if let Ok(metadata) = std::fs::metadata(entry.key()) {
+                entry.insert(mtime_millis(&metadata) / 1000);
+            }
+        }
+    }
+    times
+}
+
+// ---------------------------------------------------------------------------
+// Commands
+// ---------------------------------------------------------------------------
+
+/// Evaluate a structured query (see the module docs) against the cached
+/// index of a workspace.
+#[tauri::command]
+pub async fn query_skills(
+    window: Window,
+    state: State<'_, ScanManager>,
+    workspaces: State<'_, WorkspaceRegistry>,
+    workspace_root: String,
+    query: String,
+    limit: Option<usize>,
+) -> Result<QueryResult, String> {
+    let root = workspaces.resolve(&workspace_root)?;
+    let parsed = parse_query(&query)?;
+    let skills = index_workspace(&window, &state, &root)?;
+
+    let updated = if parsed.uses_updated() {
+        updated_times(&root, &skills)
+    } else {
+        HashMap::new()
+    };
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+
+    let mut skills = run_query(&parsed, skills, &root, &updated, now);
+    let total = skills.len();
+    if let Some(limit) = limit {
+        skills.truncate(limit);
+    }
+    Ok(QueryResult { skills, total })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::skill_index;
+
+    #[test]
+    fn test_parse_query_clauses() {
+        let parsed =
+            parse_query("type:skill,hook -category:legacy updated:<30d sort:-updated").unwrap();
+        assert_eq!(
+            parsed.clauses[0].predicate,
+            Predicate::Equals {
+                key: "type".to_string(),
+                values: vec!["skill".to_string(), "hook".to_string()],
+            }
+        );
+        assert!(parsed.clauses[1].negated);
+        assert_eq!(
+            parsed.clauses[2].predicate,
+            Predicate::Compare {
+                key: "updated".to_string(),
+                op: CompareOp::Lt,
+                operand: Operand::Age(30 * 86_400),
+            }
+        );
+        assert!(parsed.uses_updated());
+        assert_eq!(parse_date("2024-03-01"), Some(19_783 * 86_400));
+        assert!(parse_query("version:>").is_err());
+        assert!(parse_query("updated:<99999999999999999y").is_err());
+        assert!(parse_query("updated:<18446744073709551615h").is_err());
+    }
+
+    #[test]
+    fn test_run_query_filters_and_sorts() {
+        let now = 100 * 86_400;
+        let skills = vec![
+            skill_index(
+                "/ws/b.md",
+                serde_json::json!({"name": "b", "type": "subagent", "status": "draft",
+                    "tags": ["pandas"], "related": ["Py Pandas"]}),
+            ),
+            skill_index(
+                "/ws/a.md",
+                serde_json::json!({"name": "a", "type": "subagent", "status": "draft",
+                    "tags": ["pandas"], "related": ["py-pandas"], "category": "legacy"}),
+            ),
+            skill_index(
+                "/ws/c.md",
+                serde_json::json!({"name": "c", "type": "subagent", "status": "draft",
+                    "tags": ["pandas"], "related": ["py-pandas"]}),
+            ),
+            skill_index("/ws/d.md", serde_json::json!({"name": "d", "moc": true})),
+        ];
+        let updated: HashMap<PathBuf, u64> = [
+            (PathBuf::from("/ws/a.md"), 99 * 86_400),
+            (PathBuf::from("/ws/b.md"), 90 * 86_400),
+            (PathBuf::from("/ws/c.md"), 10 * 86_400),
+        ]
+        .into_iter()
+        .collect();
+        let root = Path::new("/ws");
+        let run = |query: &str| -> Vec<String> {
+            run_query(
+                &parse_query(query).unwrap(),
+                skills.clone(),
+                root,
+                &updated,
+                now,
+            )
+            .into_iter()
+            .map(|m| m.relative_path)
+            .collect()
+        };
+
+        assert_eq!(
+            run("type:subagent status:draft tag:pandas -category:legacy related:py-pandas updated:<30d"),
+            vec!["b.md"]
+        );
+        assert_eq!(
+            run("type:subagent sort:-updated"),
+            vec!["a.md", "b.md", "c.md"]
+        );
+        assert_eq!(run("sort:-status"), vec!["a.md", "b.md", "c.md", "d.md"]);
+        assert_eq!(run("type:moc"), vec!["d.md"]);
+    }
+}
@@ -0,0 +1,781 @@
+//! skill_rename.rs — Rename/move a skill and rewrite references to it.
+//!
+//! A skill is referenced by `related:` entries in other skills' frontmatter
+//! and by `[[wiki-links]]` in bodies. Both are matched with
+//! `graph_builder::normalize_id`, so anything the graph would resolve to the
+//! old skill is rewritten to the new name. Edits are made on the raw text so
+//! quoting, ordering and comments in frontmatter are left alone.
+//!
+//! `rename_skill` can run as a dry-run that only reports the planned edits.
+//! A real run applies every edit or none: each file is checked against the
+//! hash it had when planned, and files already written are restored if a
+//! later write fails.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use tauri::State;
+
+use crate::frontmatter_patch::{render_scalar, QuoteStyle};
+use crate::fs_scan::{
+    derive_name, extract_frontmatter, fallback_skill_name, workspace_markdown_files,
+    workspace_skill_files, FrontmatterFormat,
+};
+use crate::graph_builder::normalize_id;
+use crate::scan_cache::content_hash;
+use crate::skill_bundle::is_bundle_manifest;
+use crate::skill_document::parse_document;
+use crate::skill_write::{
+    check_expectation, create_new, resolve_in_workspace, write_atomic, WriteExpectation,
+};
+use crate::workspace::WorkspaceRegistry;
+
+// ---------------------------------------------------------------------------
+// Public types
+// ---------------------------------------------------------------------------
+
+/// One line whose text changes (1-based line number in the file).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LineChange {
+    pub line: usize,
+    pub before: String,
+    pub after: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileEdit {
+    /// Path the edited content is written to (the new path for the renamed
+    /// skill itself).
+    pub path: String,
+    /// `related` entries rewritten.
+    pub related: usize,
+    /// Wiki-links rewritten.
+    pub wiki_links: usize,
+    pub changes: Vec<LineChange>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenameReport {
+    pub old_path: String,
+    pub new_path: String,
+    pub old_name: String,
+    pub new_name: String,
+    pub dry_run: bool,
+    pub edits: Vec<FileEdit>,
+}
+
+/// A file edit with the content needed to apply or roll it back.
+struct PlannedFile {
+    path: PathBuf,
+    original: String,
+    updated: String,
+    edit: FileEdit,
+}
+
+// ---------------------------------------------------------------------------
+// Frontmatter rewriting
+// ---------------------------------------------------------------------------
+
+/// Continuation state for values that span several frontmatter lines.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ListState {
+    None,
+    /// Inside `[ ... ]` that has not been closed yet.
+    Bracket,
+    /// YAML block sequence (`key:` followed by `- item` lines).
+    Block,
+}
+
+/// Byte ranges of the items in a frontmatter value: the inside of quoted
+/// strings, or bare scalars. `in_list` continues a `[`-list from a previous
+/// line. Returns whether a list is still open at the end of `value`.
+fn value_item_ranges(value: &str, mut in_list: bool) -> (Vec<Range<usize>>, bool) {
+    let bytes = value.as_bytes();
+    let mut ranges = Vec::new();
+    let mut i = 0;
+
+    loop {
+        while i < bytes.len() && (bytes[i].is_ascii_whitespace() || (in_list && bytes[i] == b',')) {
+            i += 1;
+        }
+        if i >= bytes.len() || bytes[i] == b'#' {
+            return (ranges, in_list);
+        }
+
+        match bytes[i] {
+            b'[' if !in_list => {
+                in_list = true;
+                i += 1;
+                continue;
+            }
+            b']' if in_list => return (ranges, false),
+            quote @ (b'"' | b'\'') => {
+                let start = i + 1;
+                let mut end = start;
+                while end < bytes.len() && bytes[end] != quote {
+                    if quote == b'"' && bytes[end] == b'\\' {
+                        end += 1;
+                    }
+                    end += 1;
+                }
+                let end = end.min(bytes.len());
+                ranges.push(start..end);
+                i = end + 1;
+            }
+            _ => {
+                let start = i;
+                let ends_item = |at: usize| match bytes[at] {
+                    b',' | b']' => in_list,
+                    b'}' => true,
+                    b'#' => bytes[at - 1].is_ascii_whitespace(),
+                    _ => false,
+                };
+                while i < bytes.len() && !ends_item(i) {
+                    i += 1;
+                }
+                let token = value[start..i].trim_end();
+                if !token.is_empty() {
+                    ranges.push(start..start + token.len());
+                }
+            }
+        }
+
+        if !in_list {
+            return (ranges, false);
+        }
+    }
+}
+
+/// Offset just past the separator of `key` on `line`, if the line sets it.
+fn key_value_start(line: &str, format: FrontmatterFormat, key: &str) -> Option<usize> {
+    match format {
+        FrontmatterFormat::Yaml => {
+            // Top-level keys only; nested maps may reuse the key name.
+            let rest = line.strip_prefix(key)?;
+            rest.strip_prefix(':').map(|_| key.len() + 1)
+        }
+        FrontmatterFormat::Toml => {
+            let indent = line.len() - line.trim_start().len();
+            let trimmed = &line[indent..];
+            let rest = trimmed
+                .strip_prefix(key)
+                .or_else(|| trimmed.strip_prefix(&format!("\"{}\"", key)))?;
+            let after_key = indent + (trimmed.len() - rest.len());
+            let eq = rest.trim_start().strip_prefix('=')?;
+            Some(after_key + (rest.len() - eq.len()))
+        }
+        FrontmatterFormat::Json => {
+            let quoted = format!("\"{}\"", key);
+            let at = line.find(&quoted)? + quoted.len();
+            let rest = &line[at..];
+            let colon = rest.trim_start().strip_prefix(':')?;
+            Some(at + (rest.len() - colon.len()))
+        }
+    }
+}
+
+/// Rewrite the items of `key` in a frontmatter block. `rewrite` returns the
+/// replacement for a matching item. Returns the new block and edit count.
+fn rewrite_frontmatter_key(
+    block: &str,
+    format: FrontmatterFormat,
+    key: &str,
+    rewrite: &dyn Fn(&str) -> Option<String>,
+) -> (String, usize) {
+    let mut out = String::with_capacity(block.len());
+    let mut count = 0;
+    let mut state = ListState::None;
+
+    for line in block.split_inclusive('\n') {
+        let mut ranges = Vec::new();
+        let mut offset = 0;
+
+        let mut continued = false;
+        if state == ListState::Block {
+            let trimmed = line.trim_start();
+            if let Some(item) = trimmed.strip_prefix('-') {
+                offset = line.len() - item.len();
+                ranges = value_item_ranges(item, false).0;
+                continued = true;
+            } else if trimmed.is_empty() || trimmed.starts_with('#') {
+                continued = true;
+            } else {
+                state = ListState::None;
+            }
+        } else if state == ListState::Bracket {
+            let (found, open) = value_item_ranges(line, true);
+            ranges = found;
+            continued = true;
+            if !open {
+                state = ListState::None;
+            }
+        }
+
+        if !continued {
+            if let Some(start) = key_value_start(line, format, key) {
+                let value = &line[start..];
+                let (found, open) = value_item_ranges(value, false);
+                offset = start;
+                ranges = found;
+                if open {
+                    state = ListState::Bracket;
+                } else if format == FrontmatterFormat::Yaml && ranges.is_empty() {
+                    state = ListState::Block;
+                }
+            }
+        }
+
+        let mut rewritten = line.to_string();
+        for range in ranges.into_iter().rev() {
+            let range = range.start + offset..range.end + offset;
+            let Some(replacement) = rewrite(&line[range.clone()]) else {
+                continue;
+            };
+            // Replace the quotes too, so the new value can pick its own:
+            // plain YAML items are quoted when they would not read back.
+            let quote = range
+                .start
+                .checked_sub(1)
+                .map(|at| line.as_bytes()[at])
+                .filter(|b| matches!(b, b'"' | b'\''));
+            let style = match (format, quote) {
+                (FrontmatterFormat::Yaml, None) => QuoteStyle::Plain,
+                (FrontmatterFormat::Yaml, Some(b'\'')) => QuoteStyle::Single,
+                _ => QuoteStyle::Double,
+            };
+            let Some(rendered) = render_scalar(&Value::String(replacement), style) else {
+                continue;
+            };
+            let range = match quote {
+                Some(_) => range.start - 1..(range.end + 1).min(line.len()),
+                None => range,
+            };
+            rewritten.replace_range(range, &rendered);
+            count += 1;
+        }
+        out.push_str(&rewritten);
+    }
+
+    (out, count)
+}
+
+// ---------------------------------------------------------------------------
+// Body rewriting
+// ---------------------------------------------------------------------------
+
+/// Rewrite the target of every `[[target]]`, `[[target|alias]]` or
+/// `[[target#heading]]` outside code. Alias and heading parts are kept as
+/// written.
+fn rewrite_wiki_links(body: &str, rewrite: &dyn Fn(&str) -> Option<String>) -> (String, usize) {
+    let mut out = String::with_capacity(body.len());
+    let mut count = 0;
+    let mut copied = 0;
+
+    for link in parse_document(body).wiki_links {
+        let Some(replacement) = rewrite(&link.target) else {
+            continue;
+        };
+        out.push_str(&body[copied..link.target_span.start]);
+        out.push_str(&replacement);
+        copied = link.target_span.end;
+        count += 1;
+    }
+    out.push_str(&body[copied..]);
+
+    (out, count)
+}
+
+fn line_changes(before: &str, after: &str) -> Vec<LineChange> {
+    // Rewrites never add or remove lines, so lines pair up by index.
+    before
+        .lines()
+        .zip(after.lines())
+        .enumerate()
+        .filter(|(_, (old, new))| old != new)
+        .map(|(index, (old, new))| LineChange {
+            line: index + 1,
+            before: old.to_string(),
+            after: new.to_string(),
+        })
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// Planning and applying
+// ---------------------------------------------------------------------------
+
+/// Rewrite references to any of `old_ids` in one file. When `rename_self`
+/// is set, the file's own `name` field is updated to `new_name` as well.
+fn plan_file(
+    content: &str,
+    old_ids: &HashSet<String>,
+    new_name: &str,
+    rename_self: bool,
+) -> (String, usize, usize) {
+    let rewrite = |item: &str| {
+        old_ids
+            .contains(&normalize_id(item))
+            .then(|| new_name.to_string())
+    };
+
+    let (parsed, body) = extract_frontmatter(content);
+    let block = &content[..content.len() - body.len()];
+
+    let (mut block_out, mut related) = (block.to_string(), 0);
+    if let Some((frontmatter, format)) = parsed {
+        (block_out, related) = rewrite_frontmatter_key(block, format, "related", &rewrite);
+        if rename_self && frontmatter.get("name").is_some() {
+            (block_out, _) = rewrite_frontmatter_key(&block_out, format, "name", &rewrite);
+        }
+    }
+    let (body_out, wiki_links) = rewrite_wiki_links(body, &rewrite);
+
+    (block_out + &body_out, related, wiki_links)
+}
+
+/// A markdown file of the workspace other than the skill being renamed or
+/// deleted, read once for both name claims and references.
+pub(crate) struct WorkspaceFile {
+    pub path: PathBuf,
+    pub content: String,
+    /// Parsed as a skill by scans, so links can resolve to it.
+    pub is_skill: bool,
+}
+
+/// Markdown files under `root` outside `except` (a skill file or a bundle
+/// directory). Unreadable or non-UTF-8 files cannot hold references we
+/// could rewrite safely; they are left out.
+pub(crate) fn read_other_files(root: &Path, except: &Path) -> Result<Vec<WorkspaceFile>, String> {
+    let skills: HashSet<PathBuf> = workspace_skill_files(root)?.into_iter().collect();
+    Ok(workspace_markdown_files(root)?
+        .into_iter()
+        .filter(|path| !path.starts_with(except))
+        .filter_map(|path| {
+            let content = std::fs::read_to_string(&path).ok()?;
+            Some(WorkspaceFile {
+                is_skill: skills.contains(&path),
+                path,
+                content,
+            })
+        })
+        .collect())
+}
+
+/// Ids that the resolver's name and alias tiers give to skills in `others`.
+/// Those win over a file stem, so links using them are not ours.
+fn claimed_ids(others: &[WorkspaceFile]) -> HashSet<String> {
+    let mut claimed = HashSet::new();
+    for file in others.iter().filter(|file| file.is_skill) {
+        let (Some((frontmatter, _)), _) = extract_frontmatter(&file.content) else {
+            continue;
+        };
+        let aliases = frontmatter
+            .get("aliases")
+            .and_then(|v| v.as_array())
+            .into_iter()
+            .flatten()
+            .chain(frontmatter.get("slug"))
+            .filter_map(|v| v.as_str())
+            .map(normalize_id);
+        claimed.extend(aliases);
+        let name = derive_name(&Some(frontmatter.clone()), fallback_skill_name(&file.path));
+        claimed.insert(normalize_id(&name));
+    }
+    claimed
+}
+
+/// Name of the skill stored in `path` and the ids references to it may use:
+/// links may be written against the frontmatter name, or the file stem
+/// unless a skill in `others` claims it as its name or alias.
+pub(crate) fn skill_identity(
+    path: &Path,
+    content: &str,
+    others: &[WorkspaceFile],
+) -> (String, HashSet<String>) {
+    let (parsed, _) = extract_frontmatter(content);
+    let frontmatter = parsed.map(|(value, _)| value);
+    let fallback = fallback_skill_name(path);
+    let name = derive_name(&frontmatter, fallback);
+
+    let name_id = normalize_id(&name);
+    let stem_id = normalize_id(fallback);
+    let stem_is_ours = stem_id == name_id || !claimed_ids(others).contains(&stem_id);
+    let ids = [Some(name_id), stem_is_ours.then_some(stem_id)]
+        .into_iter()
+        .flatten()
+        .filter(|id| !id.is_empty())
+        .collect();
+    (name, ids)
+}
+
+/// Old name, new name and the ids whose references must be rewritten when
+/// the skill in `old` moves to `new`. Without a `requested` name the skill
+/// keeps its frontmatter name, falling back to the new file stem when it has
+/// none. Ids the moved skill still answers to are left alone, so a move that
+/// keeps both the name and the stem rewrites nothing.
+fn rename_ids(
+    old: &Path,
+    content: &str,
+    others: &[WorkspaceFile],
+    new: &Path,
+    requested: Option<String>,
+) -> Result<(String, String, HashSet<String>), String> {
+    let (old_name, mut old_ids) = skill_identity(old, content, others);
+    let new_name = match requested.map(|n| n.trim().to_string()) {
+        Some(name) if !name.is_empty() => name,
+        _ => {
+            let (parsed, _) = extract_frontmatter(content);
+            derive_name(&parsed.map(|(value, _)| value), fallback_skill_name(new))
+        }
+    };
+    let new_id = normalize_id(&new_name);
+    if new_id.is_empty() {
+        return Err(format!("Invalid skill name: {}", new_name));
+    }
+    old_ids.remove(&new_id);
+    if fallback_skill_name(new) == fallback_skill_name(old) {
+        old_ids.remove(&normalize_id(fallback_skill_name(new)));
+    }
+    Ok((old_name, new_name, old_ids))
+}
+
+/// Number of `related` entries and wiki-links in `content` pointing at any
+/// of `ids`.
+pub(crate) fn count_references(content: &str, ids: &HashSet<String>) -> usize {
+    let (_, related, wiki_links) = plan_file(content, ids, "", false);
+    related + wiki_links
+}
+
+/// Restore already-written files after a failed apply. Returns a note on
+/// anything that could not be restored.
+fn roll_back(written: &[&PlannedFile]) -> String {
+    let failed: Vec<String> = written
+        .iter()
+        .filter(|file| write_atomic(&file.path, file.original.as_bytes()).is_err())
+        .map(|file| file.path.display().to_string())
+        .collect();
+
+    if failed.is_empty() {
+        "all changes were rolled back".to_string()
+    } else {
+        format!("could not roll back: {}", failed.join(", "))
+    }
+}
+
+fn apply_plan(
+    old_path: &Path,
+    new_path: &Path,
+    own: &PlannedFile,
+    others: &[PlannedFile],
+) -> Result<(), String> {
+    // Refuse before touching anything if a file changed since it was read.
+    for file in others.iter().chain(std::iter::once(own)) {
+        let expected = WriteExpectation {
+            hash: Some(content_hash(file.original.as_bytes())),
+            mtime: None,
+        };
+        check_expectation(&file.path, &expected).map_err(|e| e.to_string())?;
+    }
+
+    let mut written: Vec<&PlannedFile> = Vec::new();
+    for file in others {
+        if let Err(error) = write_atomic(&file.path, file.updated.as_bytes()) {
+            return Err(format!("{}; {}", error, roll_back(&written)));
+        }
+        written.push(file);
+    }
+
+    if let Some(parent) = new_path.parent() {
+        if let Err(error) = std::fs::create_dir_all(parent) {
+            return Err(format!(
+                "Failed to create {}: {}; {}",
+                parent.display(),
+                error,
+                roll_back(&written)
+            ));
+        }
+    }
+    if let Err(error) = create_new(new_path, own.updated.as_bytes()) {
+        return Err(format!("{}; {}", error, roll_back(&written)));
+    }
+    if let Err(error) = std::fs::remove_file(old_path) {
+        let _ = std::fs::remove_file(new_path);
+        return Err(format!(
+            "Failed to remove {}: {}; {}",
+            old_path.display(),
+            error,
+            roll_back(&written)
+        ));
+    }
+
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Commands
+// ---------------------------------------------------------------------------
+
+/// Move the skill at `old_path` to `new_relative_path` inside
+/// `workspace_root` and rewrite `related` entries and wiki-links that point
+/// to it. The new name is `new_name`, or else the current frontmatter name,
+/// or the new file stem when there is none.
+/// With `dry_run` nothing is written and the report lists planned edits.
+#[tauri::command]
+pub async fn rename_skill(
+    workspaces: State<'_, WorkspaceRegistry>,
+    workspace_root: String,
+    old_path: String,
+    new_relative_path: String,
+    new_name: Option<String>,
+    dry_run: bool,
+) -> Result<RenameReport, String> {
+    let canonical_root = workspaces.resolve(&workspace_root)?;
+    let root = canonical_root.as_path();
+    let old =
+        std::fs::canonicalize(&old_path).map_err(|e| format!("Not a file: {}: {}", old_path, e))?;
+    if !old.is_file() || !old.starts_with(&canonical_root) {
+        return Err(format!("Not a skill file in this workspace: {}", old_path));
+    }
+    if is_bundle_manifest(&old) {
+        return Err(format!(
+            "Bundle skills are renamed by renaming their directory: {}",
+            old_path
+        ));
+    }
+
+    let new = resolve_in_workspace(root, &new_relative_path)?;
+    for path in [&old, &new] {
+        if path.extension().and_then(|e| e.to_str()) != Some("md") {
+            return Err(format!("Not a markdown file: {}", path.display()));
+        }
+    }
+    if new.exists() {
+        return Err(format!(
+            "Refusing to overwrite existing file: {}",
+            new.display()
+        ));
+    }
+
+    let own_content = std::fs::read_to_string(&old).map_err(|e| e.to_string())?;
+    let files = read_other_files(&canonical_root, &old)?;
+    let (old_name, new_name, old_ids) = rename_ids(&old, &own_content, &files, &new, new_name)?;
+
+    let (own_updated, own_related, own_links) = plan_file(&own_content, &old_ids, &new_name, true);
+    let own = PlannedFile {
+        path: old.clone(),
+        edit: FileEdit {
+            path: new.to_string_lossy().to_string(),
+            related: own_related,
+            wiki_links: own_links,
+            changes: line_changes(&own_content, &own_updated),
+        },
+        original: own_content,
+        updated: own_updated,
+    };
+
+    let mut others = Vec::new();
+    if !old_ids.is_empty() {
+        for WorkspaceFile { path, content, .. } in files {
+            let (updated, related, wiki_links) = plan_file(&content, &old_ids, &new_name, false);
+            if updated == content {
+                continue;
+            }
+            others.push(PlannedFile {
+                edit: FileEdit {
+                    path: path.to_string_lossy().to_string(),
+                    related,
+                    wiki_links,
+                    changes: line_changes(&content, &updated),
+                },
+                path,
+                original: content,
+                updated,
+            });
+        }
+    }
+
+    if !dry_run {
+        apply_plan(&old, &new, &own, &others)?;
+    }
+
+    let mut edits: Vec<FileEdit> = others.into_iter().map(|file| file.edit).collect();
+    if !own.edit.changes.is_empty() {
+        edits.insert(0, own.edit);
+    }
+
+    Ok(RenameReport {
+        old_path: old.to_string_lossy().to_string(),
+        new_path: new.to_string_lossy().to_string(),
+        old_name,
+        new_name,
+        dry_run,
+        edits,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    fn ids(names: &[&str]) -> HashSet<String> {
+        names.iter().map(|n| normalize_id(n)).collect()
+    }
+
+    #[test]
+    fn test_plan_file_rewrites_related_and_links() {
+        let content = "---\nname: other\nrelated: [\"Data Analysis\", py-pandas]\ntags:\n  - data-analysis\n---\nSee [[data-analysis|the pipeline]] and [[data_analysis#Steps]].\n```\n[[data-analysis]]\n```\n";
+        let (updated, related, links) =
+            plan_file(content, &ids(&["data-analysis"]), "data-pipeline", false);
+
+        assert_eq!(related, 1);
+        assert_eq!(links, 2);
+        assert!(updated.contains("related: [\"data-pipeline\", py-pandas]\n"));
+        // Only `related` is rewritten in frontmatter, and fenced code is left alone.
+        assert!(updated.contains("  - data-analysis\n"));
+        assert!(updated.contains("[[data-pipeline|the pipeline]] and [[data-pipeline#Steps]]"));
+        assert!(updated.contains("```\n[[data-analysis]]\n```"));
+    }
+
+    #[test]
+    fn test_skill_identity_leaves_stem_claimed_by_another_name() {
+        let dir = TempDir::new("rename-identity");
+        let own = "---\nname: py-pandas\n---\n";
+        std::fs::write(dir.join("pandas.md"), own).unwrap();
+        std::fs::write(dir.join("other.md"), "---\nname: pandas\n---\n").unwrap();
+        std::fs::write(dir.join("notes.md"), "---\nname: notes\n---\n[[pandas]]\n").unwrap();
+
+        let others = read_other_files(dir.path(), &dir.join("pandas.md")).unwrap();
+        assert_eq!(others.len(), 2);
+        let (name, own_ids) = skill_identity(&dir.join("pandas.md"), own, &others);
+        assert_eq!(name, "py-pandas");
+        assert_eq!(own_ids, ids(&["py-pandas"]));
+
+        // Unclaimed, the stem is ours again
+        let unclaimed: Vec<WorkspaceFile> = others
+            .into_iter()
+            .filter(|f| !f.path.ends_with("other.md"))
+            .collect();
+        let (_, own_ids) = skill_identity(&dir.join("pandas.md"), own, &unclaimed);
+        assert_eq!(own_ids, ids(&["py-pandas", "pandas"]));
+    }
+
+    #[test]
+    fn test_move_without_new_name_keeps_frontmatter_name() {
+        let dir = TempDir::new("rename-move");
+        let own = "---\nname: py-pandas\n---\n";
+        let old = dir.join("pandas.md");
+        std::fs::write(&old, own).unwrap();
+        std::fs::write(dir.join("plain.md"), "[[pandas]] [[py-pandas]]\n").unwrap();
+        let others = read_other_files(dir.path(), &old).unwrap();
+
+        let moved = rename_ids(&old, own, &others, &dir.join("sub/pandas.md"), None).unwrap();
+        assert_eq!(
+            moved,
+            ("py-pandas".to_string(), "py-pandas".to_string(), ids(&[]))
+        );
+        let (_, name, old_ids) = rename_ids(&old, own, &others, &dir.join("pd.md"), None).unwrap();
+        assert_eq!((name.as_str(), old_ids), ("py-pandas", ids(&["pandas"])));
+
+        // Without a frontmatter name the new stem becomes the name
+        let plain = dir.join("plain.md");
+        let (old_name, name, old_ids) =
+            rename_ids(&plain, "", &others, &dir.join("notes.md"), None).unwrap();
+        assert_eq!(
+            (old_name.as_str(), name.as_str(), old_ids),
+            ("plain", "notes", ids(&["plain"]))
+        );
+    }
+
+    #[test]
+    fn test_rewrite_frontmatter_key_handles_block_toml_and_json() {
+        let rewrite = |item: &str| (item == "a").then(|| "b".to_string());
+
+        let yaml = "name: x\nrelated:\n  - a   # first\n  - c\nmoc: false\n";
+        let (out, count) =
+            rewrite_frontmatter_key(yaml, FrontmatterFormat::Yaml, "related", &rewrite);
+        assert_eq!(
+            (out.as_str(), count),
+            ("name: x\nrelated:\n  - b   # first\n  - c\nmoc: false\n", 1)
+        );
+
+        let toml = "related = [\n  \"c\",\n  \"a\",\n]\n";
+        let (out, count) =
+            rewrite_frontmatter_key(toml, FrontmatterFormat::Toml, "related", &rewrite);
+        assert_eq!(
+            (out.as_str(), count),
+            ("related = [\n  \"c\",\n  \"b\",\n]\n", 1)
+        );
+
+        let json = "{\"name\": \"a\", \"related\": [\"a\", \"c\"]}\n";
+        let (out, count) =
+            rewrite_frontmatter_key(json, FrontmatterFormat::Json, "related", &rewrite);
+        assert_eq!(
+            (out.as_str(), count),
+            ("{\"name\": \"a\", \"related\": [\"b\", \"c\"]}\n", 1)
+        );
+    }
+
+    #[test]
+    fn test_plan_file_quotes_names_that_are_not_plain() {
+        let content = "---\nname: old\nrelated: [old, x]\nbody: |\n  old\n---\n";
+        let (updated, related, _) = plan_file(content, &ids(&["old"]), "a: b, c", true);
+        assert_eq!(related, 1);
+        let (parsed, _) = extract_frontmatter(&updated);
+        let frontmatter = parsed.unwrap().0;
+        assert_eq!(frontmatter["name"], "a: b, c");
+        assert_eq!(frontmatter["related"], serde_json::json!(["a: b, c", "x"]));
+
+        let content = "---\nrelated:\n  - old # note\n  - 'old'\n---\n";
+        let (updated, related, _) = plan_file(content, &ids(&["old"]), "it's #1", false);
+        assert_eq!(related, 2);
+        assert!(updated.contains("  - \"it's #1\" # note\n  - 'it''s #1'\n"));
+        let (parsed, _) = extract_frontmatter(&updated);
+        assert_eq!(
+            parsed.unwrap().0["related"],
+            serde_json::json!(["it's #1", "it's #1"])
+        );
+    }
+
+    #[test]
+    fn test_apply_plan_writes_nothing_on_conflict() {
+        let dir = TempDir::new("rename");
+        let old = dir.join("a.md");
+        let other = dir.join("b.md");
+        std::fs::write(&old, "---\nname: a\n---\n").unwrap();
+        std::fs::write(&other, "edited after planning").unwrap();
+
+        let planned = |path: &Path, original: &str, updated: &str| PlannedFile {
+            path: path.to_path_buf(),
+            original: original.to_string(),
+            updated: updated.to_string(),
+            edit: FileEdit {
+                path: path.to_string_lossy().to_string(),
+                related: 0,
+                wiki_links: 0,
+                changes: Vec::new(),
+            },
+        };
+        let own = planned(&old, "---\nname: a\n---\n", "---\nname: z\n---\n");
+        let others = [planned(&other, "[[a]]", "[[z]]")];
+
+        let new = dir.join("z.md");
+        assert!(apply_plan(&old, &new, &own, &others).is_err());
+        assert!(old.is_file() && !new.exists());
+        assert_eq!(
+            std::fs::read_to_string(&other).unwrap(),
+            "edited after planning"
+        );
+
+        std::fs::write(&other, "[[a]]").unwrap();
+        apply_plan(&old, &new, &own, &others).unwrap();
+        assert!(!old.exists());
+        assert_eq!(
+            std::fs::read_to_string(&new).unwrap(),
+            "---\nname: z\n---\n"
+        );
+        assert_eq!(std::fs::read_to_string(&other).unwrap(), "[[z]]");
+    }
+}
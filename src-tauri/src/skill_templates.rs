@@ -0,0 +1,437 @@
+//! skill_templates.rs — Create new skill files from templates.
+//!
+//! Built-in templates cover the node types the graph knows about (skill,
+//! subagent, hook, command, moc). A workspace can add or override templates
+//! by dropping `<name>.md` files into `.skill-templates/`. Templates may use
+//! `{{name}}`, `{{title}}` and `{{date}}` placeholders; in frontmatter their
+//! values are quoted or escaped as needed so they parse back unchanged.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::State;
+
+use crate::frontmatter_patch::{render_scalar, QuoteStyle};
+use crate::fs_scan::{extract_frontmatter, fallback_skill_name, FrontmatterFormat};
+use crate::skill_write::{create_new, resolve_in_workspace, WrittenFile};
+use crate::workspace::WorkspaceRegistry;
+
+// Also skipped by the workspace scan so templates never show up as skills.
+pub(crate) const WORKSPACE_TEMPLATE_DIR: &str = ".skill-templates";
+const DEFAULT_TEMPLATE: &str = "skill";
+
+const BUILTIN_TEMPLATES: [(&str, &str); 5] = [
+    (
+        "skill",
+        "---\nname: {{name}}\ndescription: \"\"\ntype: skill\ncategory: \"\"\ntags: []\nstatus: draft\nversion: \"0.1\"\nrelated: []\nscripts: []\nmoc: false\ncreated: {{date}}\n---\n\n# {{title}}\n\n## When to use\n\n## Steps\n",
+    ),
+    (
+        "subagent",
+        "---\nname: {{name}}\ndescription: \"\"\ntype: subagent\ntools: []\nstatus: draft\nrelated: []\nmoc: false\ncreated: {{date}}\n---\n\n# {{title}}\n\nYou are a specialised subagent. Describe the role, inputs and expected output here.\n",
+    ),
+    (
+        "hook",
+        "---\nname: {{name}}\ndescription: \"\"\ntype: hook\nevent: \"\"\nstatus: draft\nscripts: []\nrelated: []\nmoc: false\ncreated: {{date}}\n---\n\n# {{title}}\n\n## Trigger\n\n## Action\n",
+    ),
+    (
+        "command",
+        "---\nname: {{name}}\ndescription: \"\"\ntype: command\nargument-hint: \"\"\nstatus: draft\nrelated: []\nmoc: false\ncreated: {{date}}\n---\n\n# {{title}}\n\n$ARGUMENTS\n",
+    ),
+    (
+        "moc",
+        "---\nname: {{name}}\ndescription: \"Map of Content for {{title}}\"\ntype: moc\ncategory: \"\"\ntags: [overview, navigation]\nstatus: draft\nrelated: []\nmoc: true\ncreated: {{date}}\n---\n\n# {{title}}\n\n## Skills in this cluster\n\n- [[]]\n",
+    ),
+];
+
+// ---------------------------------------------------------------------------
+// Public types
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TemplateSource {
+    Builtin,
+    Workspace,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkillTemplateInfo {
+    pub name: String,
+    pub source: TemplateSource,
+}
+
+// ---------------------------------------------------------------------------
+// Template lookup and rendering
+// ---------------------------------------------------------------------------
+
+/// Template names double as file names, so keep them to a safe alphabet.
+fn is_valid_template_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Workspace template first (so it can override a built-in), then built-in.
+/// A workspace template must resolve inside `workspace_root` (canonical),
+/// so a symlink cannot copy an outside file into a new skill.
+fn load_template(workspace_root: &Path, name: &str) -> Result<String, String> {
+    if !is_valid_template_name(name) {
+        return Err(format!("Invalid template name: {}", name));
+    }
+
+    let custom = workspace_root
+        .join(WORKSPACE_TEMPLATE_DIR)
+        .join(format!("{}.md", name));
+    if let Ok(canonical) = custom.canonicalize() {
+        if !canonical.starts_with(workspace_root) {
+            return Err(format!(
+                "Template is outside the workspace: {}",
+                custom.display()
+            ));
+        }
+        if canonical.is_file() {
+            return std::fs::read_to_string(&canonical)
+                .map_err(|e| format!("Failed to read template {}: {}", custom.display(), e));
+        }
+    }
+
+    BUILTIN_TEMPLATES
+        .iter()
+        .find(|(builtin, _)| *builtin == name)
+        .map(|(_, body)| body.to_string())
+        .ok_or_else(|| format!("Unknown template: {}", name))
+}
+
+fn title_case(name: &str) -> String {
+    name.split(['-', '_', ' '])
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// Format and byte length of the frontmatter block a template opens with.
+/// YAML and TOML blocks are found by their fences, since placeholders keep
+/// them from parsing; JSON ones only hold placeholders inside strings.
+fn frontmatter_extent(template: &str) -> Option<(FrontmatterFormat, usize)> {
+    for (fence, format) in [
+        ("---", FrontmatterFormat::Yaml),
+        ("+++", FrontmatterFormat::Toml),
+    ] {
+        let Some(first_line) = template.strip_prefix(fence) else {
+            continue;
+        };
+        let mut at = template.len() - first_line.len();
+        for line in first_line.split_inclusive('\n').skip(1) {
+            at += line.len();
+            if line.trim_end() == fence {
+                return Some((format, at));
+            }
+        }
+        return None;
+    }
+    match extract_frontmatter(template) {
+        (Some((_, format)), body) => Some((format, template.len() - body.len())),
+        (None, _) => None,
+    }
+}
+
+/// `text` escaped for the inside of a JSON string, which is also a valid
+/// TOML basic string body.
+fn escape_basic(text: &str) -> String {
+    let literal = serde_json::to_string(text).unwrap_or_default();
+    literal[1..literal.len() - 1].to_string()
+}
+
+/// Fill the placeholders in one frontmatter line so each value reads back
+/// as written: escaped inside a quoted string, a quoted scalar where a
+/// plain one would not parse. A TOML literal string (`'...'`) has no
+/// escapes, so one that would receive a value it cannot hold is turned into
+/// a basic string.
+fn fill_frontmatter_line(
+    line: &str,
+    format: FrontmatterFormat,
+    values: &[(&str, String)],
+) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut quote: Option<char> = None;
+    // Where the open quote sits in `out`, and whether a TOML literal string
+    // is being rewritten as a basic one
+    let mut opened_at = 0;
+    let mut as_basic = false;
+    let mut rest = line;
+
+    while let Some(c) = rest.chars().next() {
+        if let Some((placeholder, value)) = values.iter().find(|(p, _)| rest.starts_with(p)) {
+            let toml_literal = quote == Some('\'') && format == FrontmatterFormat::Toml;
+            if toml_literal && !as_basic && value.chars().any(|c| c == '\'' || c.is_control()) {
+                let written = escape_basic(&out[opened_at + 1..]);
+                out.truncate(opened_at);
+                out.push('"');
+                out.push_str(&written);
+                as_basic = true;
+            }
+            let filled = match quote {
+                Some('"') => escape_basic(value),
+                Some(_) if as_basic => escape_basic(value),
+                Some(_) if toml_literal => value.clone(),
+                Some(_) => value.replace('\'', "''"),
+                None => {
+                    let style = if format == FrontmatterFormat::Yaml {
+                        QuoteStyle::Plain
+                    } else {
+                        QuoteStyle::Double
+                    };
+                    render_scalar(&Value::String(value.clone()), style).unwrap_or_default()
+                }
+            };
+            out.push_str(&filled);
+            rest = &rest[placeholder.len()..];
+            continue;
+        }
+
+        let mut taken = c.len_utf8();
+        match (quote, c) {
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                opened_at = out.len();
+            }
+            // Keep an escaped character, quote included, inside the string
+            (Some('"'), '\\') => taken += rest[1..].chars().next().map_or(0, char::len_utf8),
+            (Some('\''), '\'') if as_basic => {
+                out.push('"');
+                rest = &rest[taken..];
+                quote = None;
+                as_basic = false;
+                continue;
+            }
+            (Some(_), c) if as_basic => {
+                out.push_str(&escape_basic(&c.to_string()));
+                rest = &rest[taken..];
+                continue;
+            }
+            (Some(q), c) if c == q => quote = None,
+            _ => {}
+        }
+        out.push_str(&rest[..taken]);
+        rest = &rest[taken..];
+    }
+    out
+}
+
+fn render_template(template: &str, name: &str, date: &str) -> String {
+    let values = [
+        ("{{name}}", name.to_string()),
+        ("{{title}}", title_case(name)),
+        ("{{date}}", date.to_string()),
+    ];
+    let (format, split) = frontmatter_extent(template).unwrap_or((FrontmatterFormat::Yaml, 0));
+    let (frontmatter, body) = template.split_at(split);
+
+    let mut out: String = frontmatter
+        .split_inclusive('\n')
+        .map(|line| fill_frontmatter_line(line, format, &values))
+        .collect();
+    let body = values
+        .iter()
+        .fold(body.to_string(), |body, (placeholder, value)| {
+            body.replace(placeholder, value)
+        });
+    out.push_str(&body);
+    out
+}
+
+/// Today's date (UTC) as `YYYY-MM-DD`.
+fn today() -> String {
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() / 86_400)
+        .unwrap_or(0) as i64;
+    let (year, month, day) = civil_from_days(days);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Days since 1970-01-01 to a proleptic Gregorian date (Howard Hinnant's
+/// `civil_from_days`).
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+// ---------------------------------------------------------------------------
+// Commands
+// ---------------------------------------------------------------------------
+
+/// List the templates available to `create_skill_file` in a workspace.
+#[tauri::command]
+pub async fn list_skill_templates(
+    workspaces: State<'_, WorkspaceRegistry>,
+    workspace_root: String,
+) -> Result<Vec<SkillTemplateInfo>, String> {
+    let root = workspaces.resolve(&workspace_root)?;
+    let mut templates: Vec<SkillTemplateInfo> = BUILTIN_TEMPLATES
+        .iter()
+        .map(|(name, _)| SkillTemplateInfo {
+            name: name.to_string(),
+            source: TemplateSource::Builtin,
+        })
+        .collect();
+
+    let dir = root.join(WORKSPACE_TEMPLATE_DIR);
+    if let Ok(entries) = std::fs::read_dir(&dir) {
+        let mut custom: Vec<String> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().and_then(|e| e.to_str()) == Some("md"))
+            .filter_map(|path| path.file_stem().and_then(|s| s.to_str()).map(String::from))
+            .filter(|name| is_valid_template_name(name))
+            .collect();
+        custom.sort();
+
+        for name in custom {
+            templates.retain(|t| t.name != name);
+            templates.push(SkillTemplateInfo {
+                name,
+                source: TemplateSource::Workspace,
+            });
+        }
+    }
+
+    Ok(templates)
+}
+
+/// Create a new skill file at `relative_path` inside `workspace_root` from
+/// a template. Security: the path must stay inside the workspace, must be a
+/// `.md` file, and must not exist yet.
+#[tauri::command]
+pub async fn create_skill_file(
+    workspaces: State<'_, WorkspaceRegistry>,
+    workspace_root: String,
+    relative_path: String,
+    template: Option<String>,
+    name: Option<String>,
+) -> Result<WrittenFile, String> {
+    let root = workspaces.resolve(&workspace_root)?;
+    let root = root.as_path();
+    let target = resolve_in_workspace(root, &relative_path)?;
+
+    let ext = target.extension().and_then(|e| e.to_str()).unwrap_or("");
+    if ext != "md" {
+        return Err(format!(
+            "Refusing to create non-markdown file: {}",
+            relative_path
+        ));
+    }
+
+    let name = name
+        .map(|n| n.trim().to_string())
+        .filter(|n| !n.is_empty())
+        .unwrap_or_else(|| fallback_skill_name(&target).to_string());
+
+    let template = template.as_deref().unwrap_or(DEFAULT_TEMPLATE);
+    let content = render_template(&load_template(root, template)?, &name, &today());
+
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+
+    create_new(&target, content.as_bytes()).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_template_fills_variables() {
+        let rendered = render_template(
+            &load_template(Path::new("/nonexistent"), "moc").unwrap(),
+            "data-tools",
+            "2024-03-01",
+        );
+        assert!(rendered.contains("name: data-tools\n"));
+        assert!(rendered.contains("# Data Tools\n"));
+        assert!(rendered.contains("created: 2024-03-01\n"));
+        assert!(load_template(Path::new("/nonexistent"), "../secret").is_err());
+    }
+
+    #[test]
+    fn test_render_template_quotes_values_in_frontmatter() {
+        for template in ["skill", "moc"] {
+            let rendered = render_template(
+                &load_template(Path::new("/nonexistent"), template).unwrap(),
+                "Foo: bar #1",
+                "2024-03-01",
+            );
+            let (parsed, body) = extract_frontmatter(&rendered);
+            let frontmatter = parsed.unwrap().0;
+            assert_eq!(frontmatter["name"], "Foo: bar #1");
+            assert_eq!(frontmatter["created"], "2024-03-01");
+            assert!(body.contains("# Foo: Bar #1\n"));
+            if template == "moc" {
+                assert_eq!(frontmatter["description"], "Map of Content for Foo: Bar #1");
+            }
+        }
+
+        let toml = render_template(
+            "+++\nname = {{name}}\ntitle = \"{{title}}\"\n+++\n",
+            "say \"hi\"",
+            "",
+        );
+        let (parsed, _) = extract_frontmatter(&toml);
+        let frontmatter = parsed.unwrap().0;
+        assert_eq!(frontmatter["title"], "Say \"hi\"");
+        assert_eq!(frontmatter["name"], "say \"hi\"");
+
+        // A literal string cannot hold an apostrophe, so it becomes a basic one
+        let template = "+++\nlabel = 'Skill {{name}} in C:\\tools'\n+++\n";
+        let toml = render_template(template, "Bob's tool", "");
+        let (parsed, _) = extract_frontmatter(&toml);
+        assert_eq!(parsed.unwrap().0["label"], "Skill Bob's tool in C:\\tools");
+        let toml = render_template(template, "plain", "");
+        assert!(toml.contains("label = 'Skill plain in C:\\tools'\n"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_load_template_stays_inside_workspace() {
+        use crate::test_support::TempDir;
+
+        let root = TempDir::new("templates");
+        let outside = TempDir::new("templates-outside");
+        let dir = root.join(WORKSPACE_TEMPLATE_DIR);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("mine.md"), "---\nname: {{name}}\n---\n").unwrap();
+        std::fs::write(outside.join("secret.md"), "secret").unwrap();
+        std::os::unix::fs::symlink(outside.join("secret.md"), dir.join("evil.md")).unwrap();
+
+        assert_eq!(
+            load_template(root.path(), "mine").unwrap(),
+            "---\nname: {{name}}\n---\n"
+        );
+        assert!(load_template(root.path(), "evil").is_err());
+    }
+
+    #[test]
+    fn test_civil_from_days() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(19_782), (2024, 2, 29));
+    }
+}
@@ -0,0 +1,371 @@
+//! skill_trash.rs — Safe delete for skill files, with restore and purge.
+//!
+//! `delete_skill` never unlinks anything: it moves the skill (or the whole
+//! directory of a `SKILL.md` bundle) into a trash folder next to a
+//! `meta.json` recording where it came from, and reports which skills still
+//! reference it. Like the scan cache, the trash lives in `.git/` when the
+//! workspace is a repository root (so neither the scan nor autogit sees it),
+//! otherwise under the app data dir, one folder per canonical workspace root.
+//!
+//! Layout: `<trash>/<item id>/meta.json` + `<trash>/<item id>/<file name>`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager, State};
+
+use crate::fs_scan::workspace_markdown_files;
+use crate::scan_cache::content_hash;
+use crate::skill_bundle::is_bundle_manifest;
+use crate::skill_rename::{count_references, read_other_files, skill_identity, WorkspaceFile};
+use crate::workspace::WorkspaceRegistry;
+
+const TRASH_DIR_NAME: &str = "fv-skills-trash";
+const TRASH_META_FILE: &str = "meta.json";
+
+// ---------------------------------------------------------------------------
+// Public types
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashItem {
+    pub id: String,
+    pub name: String,
+    /// Absolute path the skill had before it was deleted.
+    pub original_path: String,
+    /// Milliseconds since the Unix epoch.
+    pub deleted_at: u64,
+    /// True when the whole bundle directory was trashed with its `SKILL.md`.
+    pub is_bundle: bool,
+}
+
+/// A skill that still points at a deleted one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkillReference {
+    pub path: String,
+    /// Number of `related` entries and wiki-links pointing at the deleted skill.
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeleteReport {
+    pub item: TrashItem,
+    pub referenced_by: Vec<SkillReference>,
+}
+
+// ---------------------------------------------------------------------------
+// Trash location and moves
+// ---------------------------------------------------------------------------
+
+fn trash_dir_for(root: &Path, app_data_dir: Option<PathBuf>) -> Result<PathBuf, String> {
+    let git_dir = root.join(".git");
+    if git_dir.is_dir() {
+        return Ok(git_dir.join(TRASH_DIR_NAME));
+    }
+
+    let root_hash = content_hash(root.to_string_lossy().as_bytes());
+    app_data_dir
+        .map(|dir| dir.join("trash").join(&root_hash[..16]))
+        .ok_or_else(|| "No trash location available for this workspace".to_string())
+}
+
+fn workspace_trash(
+    app: &AppHandle,
+    workspaces: &WorkspaceRegistry,
+    workspace_root: &str,
+) -> Result<(PathBuf, PathBuf), String> {
+    let root = workspaces.resolve(workspace_root)?;
+    let trash = trash_dir_for(&root, app.path().app_data_dir().ok())?;
+    Ok((root, trash))
+}
+
+/// Item ids come from the frontend on restore/purge; only accept the shape
+/// `delete_skill` generates so an id can never name a path outside the trash.
+fn item_dir(trash: &Path, id: &str) -> Result<PathBuf, String> {
+    let valid = !id.is_empty() && id.chars().all(|c| c.is_ascii_hexdigit() || c == '-');
+    if !valid {
+        return Err(format!("Invalid trash item id: {}", id));
+    }
+    let dir = trash.join(id);
+    if !dir.is_dir() {
+        return Err(format!("No such trash item: {}", id));
+    }
+    Ok(dir)
+}
+
+fn read_item(dir: &Path) -> Result<TrashItem, String> {
+    let raw = std::fs::read_to_string(dir.join(TRASH_META_FILE))
+        .map_err(|e| format!("Failed to read trash metadata in {}: {}", dir.display(), e))?;
+    serde_json::from_str(&raw).map_err(|e| format!("Invalid trash metadata: {}", e))
+}
+
+/// The trashed file or directory inside an item folder.
+fn payload_path(dir: &Path, item: &TrashItem) -> Result<PathBuf, String> {
+    let original = Path::new(&item.original_path);
+    let source = if item.is_bundle {
+        original.parent()
+    } else {
+        Some(original)
+    };
+    source
+        .and_then(|p| p.file_name())
+        .map(|name| dir.join(name))
+        .ok_or_else(|| format!("Invalid original path: {}", item.original_path))
+}
+
+fn copy_recursive(from: &Path, to: &Path) -> std::io::Result<()> {
+    if from.is_dir() {
+        std::fs::create_dir_all(to)?;
+        for entry in std::fs::read_dir(from)? {
+            let entry = entry?;
+            copy_recursive(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        Ok(())
+    } else {
+        std::fs::copy(from, to).map(|_| ())
+    }
+}
+
+/// Rename, falling back to copy + remove when `from` and `to` are on
+/// different filesystems (e.g. app data dir on another volume).
+fn move_path(from: &Path, to: &Path) -> Result<(), String> {
+    if std::fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+
+    copy_recursive(from, to).map_err(|e| {
+        let _ = std::fs::remove_dir_all(to);
+        let _ = std::fs::remove_file(to);
+        format!("Failed to move {}: {}", from.display(), e)
+    })?;
+    let removed = if from.is_dir() {
+        std::fs::remove_dir_all(from)
+    } else {
+        std::fs::remove_file(from)
+    };
+    removed.map_err(|e| format!("Moved {} but could not remove it: {}", from.display(), e))
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Files among `others` whose `related` or wiki-links point at `ids`.
+fn find_referrers(others: &[WorkspaceFile], ids: &HashSet<String>) -> Vec<SkillReference> {
+    others
+        .iter()
+        .filter_map(|file| {
+            let count = count_references(&file.content, ids);
+            (count > 0).then(|| SkillReference {
+                path: file.path.to_string_lossy().to_string(),
+                count,
+            })
+        })
+        .collect()
+}
+
+fn trash_skill(root: &Path, trash: &Path, file: &Path) -> Result<DeleteReport, String> {
+    let content = std::fs::read_to_string(file).map_err(|e| e.to_string())?;
+    let is_bundle = is_bundle_manifest(file);
+    let source = if is_bundle {
+        file.parent().filter(|dir| *dir != root)
+    } else {
+        Some(file)
+    }
+    .ok_or_else(|| "Refusing to trash the workspace root".to_string())?;
+    let others = read_other_files(root, source).unwrap_or_default();
+    let (name, ids) = skill_identity(file, &content, &others);
+
+    let deleted_at = now_millis();
+    let id = format!(
+        "{:x}-{}",
+        deleted_at,
+        &content_hash(file.to_string_lossy().as_bytes())[..8]
+    );
+    let item = TrashItem {
+        id: id.clone(),
+        name,
+        original_path: file.to_string_lossy().to_string(),
+        deleted_at,
+        is_bundle,
+    };
+
+    let dir = trash.join(&id);
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let meta = serde_json::to_string_pretty(&item).map_err(|e| e.to_string())?;
+    std::fs::write(dir.join(TRASH_META_FILE), meta)
+        .map_err(|e| format!("Failed to write trash metadata: {}", e))?;
+
+    if let Err(error) = move_path(source, &payload_path(&dir, &item)?) {
+        let _ = std::fs::remove_dir_all(&dir);
+        return Err(error);
+    }
+
+    let referenced_by = find_referrers(&others, &ids);
+    Ok(DeleteReport {
+        item,
+        referenced_by,
+    })
+}
+
+/// `meta.json` is only as trustworthy as the trash directory, so the
+/// original path must still land inside the workspace.
+fn restore_item(root: &Path, trash: &Path, id: &str) -> Result<TrashItem, String> {
+    let dir = item_dir(trash, id)?;
+    let item = read_item(&dir)?;
+    let original = Path::new(&item.original_path);
+    let escapes = original
+        .components()
+        .any(|c| matches!(c, std::path::Component::ParentDir));
+    if escapes || !original.starts_with(root) || original == root {
+        return Err(format!(
+            "Cannot restore outside the workspace: {}",
+            item.original_path
+        ));
+    }
+    let target = if item.is_bundle {
+        original.parent().unwrap_or(original)
+    } else {
+        original
+    };
+
+    if target.exists() {
+        return Err(format!(
+            "Cannot restore: {} already exists",
+            target.display()
+        ));
+    }
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+
+    move_path(&payload_path(&dir, &item)?, target)?;
+    let _ = std::fs::remove_dir_all(&dir);
+    Ok(item)
+}
+
+fn list_items(trash: &Path) -> Vec<TrashItem> {
+    let mut items: Vec<TrashItem> = std::fs::read_dir(trash)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|entry| read_item(&entry.path()).ok())
+                .collect()
+        })
+        .unwrap_or_default();
+    items.sort_by_key(|item| std::cmp::Reverse(item.deleted_at));
+    items
+}
+
+// ---------------------------------------------------------------------------
+// Commands
+// ---------------------------------------------------------------------------
+
+/// Move a skill file (or its bundle directory) to the workspace trash and
+/// list the skills that still reference it.
+#[tauri::command]
+pub async fn delete_skill(
+    app: AppHandle,
+    workspaces: State<'_, WorkspaceRegistry>,
+    workspace_root: String,
+    file_path: String,
+) -> Result<DeleteReport, String> {
+    let (root, trash) = workspace_trash(&app, &workspaces, &workspace_root)?;
+    let file = workspaces.resolve(&file_path)?;
+
+    if !file.is_file() || !file.starts_with(&root) || file.starts_with(&trash) {
+        return Err(format!("Not a skill file in this workspace: {}", file_path));
+    }
+    if file.extension().and_then(|e| e.to_str()) != Some("md") {
+        return Err(format!(
+            "Refusing to delete non-markdown file: {}",
+            file_path
+        ));
+    }
+
+    trash_skill(&root, &trash, &file)
+}
+
+/// Trashed items for a workspace, most recently deleted first.
+#[tauri::command]
+pub async fn list_trash(
+    app: AppHandle,
+    workspaces: State<'_, WorkspaceRegistry>,
+    workspace_root: String,
+) -> Result<Vec<TrashItem>, String> {
+    let (_, trash) = workspace_trash(&app, &workspaces, &workspace_root)?;
+    Ok(list_items(&trash))
+}
+
+/// Move a trashed item back to its original path. Fails rather than
+/// overwriting if something now exists there.
+#[tauri::command]
+pub async fn restore_trash_item(
+    app: AppHandle,
+    workspaces: State<'_, WorkspaceRegistry>,
+    workspace_root: String,
+    id: String,
+) -> Result<TrashItem, String> {
+    let (root, trash) = workspace_trash(&app, &workspaces, &workspace_root)?;
+    restore_item(&root, &trash, &id)
+}
+
+/// Permanently delete one trashed item, or the whole trash when `id` is
+/// `None`. Returns how many items were purged.
+#[tauri::command]
+pub async fn purge_trash(
+    app: AppHandle,
+    workspaces: State<'_, WorkspaceRegistry>,
+    workspace_root: String,
+    id: Option<String>,
+) -> Result<usize, String> {
+    let (_, trash) = workspace_trash(&app, &workspaces, &workspace_root)?;
+    let dirs = match id {
+        Some(id) => vec![item_dir(&trash, &id)?],
+        None => list_items(&trash)
+            .into_iter()
+            .map(|item| trash.join(item.id))
+            .collect(),
+    };
+
+    for dir in &dirs {
+        std::fs::remove_dir_all(dir)
+            .map_err(|e| format!("Failed to purge {}: {}", dir.display(), e))?;
+    }
+    Ok(dirs.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    #[test]
+    fn test_trash_and_restore_round_trip() {
+        let dir = TempDir::new("trash");
+        let root = dir.path();
+        std::fs::create_dir_all(root.join(".git")).unwrap();
+        let skill = root.join("pandas.md");
+        let other = root.join("analysis.md");
+        std::fs::write(&skill, "---\nname: py-pandas\n---\n").unwrap();
+        std::fs::write(&other, "---\nrelated: [py-pandas]\n---\nSee [[pandas]].\n").unwrap();
+
+        let trash = trash_dir_for(root, None).unwrap();
+        let report = trash_skill(root, &trash, &skill).unwrap();
+        assert!(!skill.exists());
+        assert_eq!(report.referenced_by.len(), 1);
+        assert_eq!(report.referenced_by[0].count, 2);
+        assert_eq!(list_items(&trash).len(), 1);
+
+        assert!(item_dir(&trash, "../..").is_err());
+        restore_item(root, &trash, &report.item.id).unwrap();
+        assert!(skill.is_file());
+        assert!(list_items(&trash).is_empty());
+    }
+}
@@ -0,0 +1,354 @@
+//! skill_write.rs — Crash-safe, conflict-checked writes of skill files.
+//!
+//! Content goes to a temp file in the target's directory, is fsynced, and is
+//! then renamed over the target, so a crash leaves either the old or the new
+//! file and never a truncated one. Callers may pass the content hash and/or
+//! mtime they last read; if the file on disk no longer matches, the write is
+//! refused with `WriteError::Conflict` instead of clobbering an outside edit.
+//! New files are created without ever replacing an existing one, at paths
+//! confined to the workspace.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::scan_cache::{content_hash, mtime_millis};
+
+// ---------------------------------------------------------------------------
+// Public types
+// ---------------------------------------------------------------------------
+
+/// What the caller believes is on disk. Unset fields are not checked.
+#[derive(Debug, Clone, Default)]
+pub struct WriteExpectation {
+    pub hash: Option<String>,
+    /// Milliseconds since the Unix epoch, as reported by `read_skill_file`.
+    pub mtime: Option<u64>,
+}
+
+/// State of the file after a successful write, to be used as the
+/// expectation for the next save.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WrittenFile {
+    pub path: String,
+    pub content_hash: String,
+    pub mtime: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum WriteError {
+    NotFound {
+        path: String,
+    },
+    NotMarkdown {
+        path: String,
+    },
+    AlreadyExists {
+        path: String,
+    },
+    /// The path resolves outside every open workspace.
+    OutsideWorkspace {
+        path: String,
+    },
+    /// The file changed on disk since the caller read it.
+    Conflict {
+        path: String,
+        expected_hash: Option<String>,
+        actual_hash: String,
+        expected_mtime: Option<u64>,
+        actual_mtime: u64,
+    },
+    Io {
+        path: String,
+        message: String,
+    },
+}
+
+impl fmt::Display for WriteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WriteError::NotFound { path } => write!(f, "Not a file: {}", path),
+            WriteError::NotMarkdown { path } => {
+                write!(f, "Refusing to write non-markdown file: {}", path)
+            }
+            WriteError::AlreadyExists { path } => {
+                write!(f, "Refusing to overwrite existing file: {}", path)
+            }
+            WriteError::OutsideWorkspace { path } => {
+                write!(f, "Path is outside the open workspace: {}", path)
+            }
+            WriteError::Conflict { path, .. } => {
+                write!(f, "File changed on disk since it was loaded: {}", path)
+            }
+            WriteError::Io { path, message } => write!(f, "Write failed for {}: {}", path, message),
+        }
+    }
+}
+
+impl WriteError {
+    fn io(path: &Path, error: std::io::Error) -> Self {
+        WriteError::Io {
+            path: path.to_string_lossy().to_string(),
+            message: error.to_string(),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Writes
+// ---------------------------------------------------------------------------
+
+/// Compare the file at `path` against `expected`, returning a `Conflict`
+/// when either the hash or the mtime differs.
+pub fn check_expectation(path: &Path, expected: &WriteExpectation) -> Result<(), WriteError> {
+    if expected.hash.is_none() && expected.mtime.is_none() {
+        return Ok(());
+    }
+
+    let metadata = std::fs::metadata(path).map_err(|e| WriteError::io(path, e))?;
+    let bytes = std::fs::read(path).map_err(|e| WriteError::io(path, e))?;
+    check_snapshot(path, &bytes, mtime_millis(&metadata), expected)
+}
+
+/// As `check_expectation`, for `bytes` and `actual_mtime` the caller already
+/// read from `path`, so the check covers exactly the content it edits.
+pub fn check_snapshot(
+    path: &Path,
+    bytes: &[u8],
+    actual_mtime: u64,
+    expected: &WriteExpectation,
+) -> Result<(), WriteError> {
+    if expected.hash.is_none() && expected.mtime.is_none() {
+        return Ok(());
+    }
+
+    let actual_hash = content_hash(bytes);
+    let hash_changed = expected.hash.as_ref().is_some_and(|h| *h != actual_hash);
+    let mtime_changed = expected.mtime.is_some_and(|m| m != actual_mtime);
+    if hash_changed || mtime_changed {
+        return Err(WriteError::Conflict {
+            path: path.to_string_lossy().to_string(),
+            expected_hash: expected.hash.clone(),
+            actual_hash,
+            expected_mtime: expected.mtime,
+            actual_mtime,
+        });
+    }
+
+    Ok(())
+}
+
+/// Replace (or create) `path` with `bytes` via temp file + fsync + rename.
+/// Permissions of an existing file are carried over.
+pub fn write_atomic(path: &Path, bytes: &[u8]) -> Result<WrittenFile, WriteError> {
+    let permissions = std::fs::metadata(path).ok().map(|m| m.permissions());
+    let tmp_path = write_temp(path, bytes, permissions).map_err(|e| WriteError::io(path, e))?;
+
+    if let Err(error) = std::fs::rename(&tmp_path, path) {
+        let _ = std::fs::remove_file(&tmp_path);
+        return Err(WriteError::io(path, error));
+    }
+
+    finish_write(path, bytes)
+}
+
+/// Create `path` with `bytes`, failing with `AlreadyExists` rather than
+/// replacing a file that is already there. The content is fsynced in a temp
+/// file first and hard-linked into place, so the new file is never partial.
+pub fn create_new(path: &Path, bytes: &[u8]) -> Result<WrittenFile, WriteError> {
+    let tmp_path = write_temp(path, bytes, None).map_err(|e| WriteError::io(path, e))?;
+
+    let result = std::fs::hard_link(&tmp_path, path);
+    let _ = std::fs::remove_file(&tmp_path);
+    match result {
+        Ok(()) => finish_write(path, bytes),
+        Err(error) if error.kind() == std::io::ErrorKind::AlreadyExists => {
+            Err(WriteError::AlreadyExists {
+                path: path.to_string_lossy().to_string(),
+            })
+        }
+        Err(error) => Err(WriteError::io(path, error)),
+    }
+}
+
+/// Write and fsync `bytes` to a new temp file next to `path`, returning its
+/// path. A name already taken, e.g. by a file left behind by a crash, is
+/// skipped rather than reused; on error only a file this call created is
+/// removed.
+fn write_temp(
+    path: &Path,
+    bytes: &[u8],
+    permissions: Option<std::fs::Permissions>,
+) -> std::io::Result<PathBuf> {
+    let (tmp_path, mut file) = loop {
+        let tmp_path = temp_path_for(path);
+        match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&tmp_path)
+        {
+            Ok(file) => break (tmp_path, file),
+            Err(error) if error.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(error) => return Err(error),
+        }
+    };
+
+    let result = file.write_all(bytes).and_then(|_| {
+        if let Some(permissions) = permissions {
+            file.set_permissions(permissions)?;
+        }
+        file.sync_all()
+    });
+    match result {
+        Ok(()) => Ok(tmp_path),
+        Err(error) => {
+            let _ = std::fs::remove_file(&tmp_path);
+            Err(error)
+        }
+    }
+}
+
+/// Sync the directory entry and report the file's new state.
+fn finish_write(path: &Path, bytes: &[u8]) -> Result<WrittenFile, WriteError> {
+    let dir = path
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    sync_dir(dir);
+
+    let metadata = std::fs::metadata(path).map_err(|e| WriteError::io(path, e))?;
+    Ok(WrittenFile {
+        path: path.to_string_lossy().to_string(),
+        content_hash: content_hash(bytes),
+        mtime: mtime_millis(&metadata),
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Workspace paths
+// ---------------------------------------------------------------------------
+
+/// Resolve `relative` inside the workspace `root`. Absolute paths, `..`
+/// components and symlinked directories leading outside the workspace are
+/// refused.
+pub fn resolve_in_workspace(root: &Path, relative: &str) -> Result<PathBuf, String> {
+    let root = std::fs::canonicalize(root)
+        .map_err(|e| format!("Workspace not found: {}: {}", root.display(), e))?;
+
+    let relative_path = Path::new(relative);
+    if relative.trim().is_empty() {
+        return Err("Path must not be empty".to_string());
+    }
+    let lexically_inside = relative_path
+        .components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+    if !lexically_inside {
+        return Err(format!("Path escapes the workspace: {}", relative));
+    }
+
+    let target = root.join(relative_path);
+    // The target may not exist yet; check the deepest part that does, which
+    // is where a symlink could redirect the write.
+    let existing = target
+        .ancestors()
+        .find(|p| p.exists())
+        .unwrap_or(root.as_path());
+    let resolved = std::fs::canonicalize(existing).map_err(|e| e.to_string())?;
+    if !resolved.starts_with(&root) {
+        return Err(format!("Path escapes the workspace: {}", relative));
+    }
+
+    Ok(target)
+}
+
+/// Hidden sibling of `path`, unique per process and per call so concurrent
+/// writes of the same file, from this process or another, never share a
+/// temp file.
+fn temp_path_for(path: &Path) -> PathBuf {
+    static NEXT_TEMP: AtomicU64 = AtomicU64::new(0);
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let n = NEXT_TEMP.fetch_add(1, Ordering::Relaxed);
+    path.with_file_name(format!(".{}.{}.{}.tmp", name, std::process::id(), n))
+}
+
+/// Persist the rename itself. Directories cannot be opened for sync on
+/// Windows, where the rename is already durable enough for our purposes.
+fn sync_dir(dir: &Path) {
+    if cfg!(unix) {
+        if let Ok(handle) = File::open(dir) {
+            let _ = handle.sync_all();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    fn temp_file(label: &str, content: &str) -> (TempDir, PathBuf) {
+        let dir = TempDir::new(&format!("skill-write-{}", label));
+        let path = dir.join("skill.md");
+        std::fs::write(&path, content).unwrap();
+        (dir, path)
+    }
+
+    #[test]
+    fn test_write_atomic_replaces_content_without_leftovers() {
+        let (dir, path) = temp_file("atomic", "old");
+        let written = write_atomic(&path, b"new").unwrap();
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "new");
+        assert_eq!(written.content_hash, content_hash(b"new"));
+        let leftovers = std::fs::read_dir(dir.path()).unwrap().count();
+        assert_eq!(leftovers, 1);
+
+        // Concurrent saves of one file each get their own temp file
+        let writers: Vec<_> = (0..8)
+            .map(|i| {
+                let path = path.clone();
+                std::thread::spawn(move || write_atomic(&path, format!("v{}", i).as_bytes()))
+            })
+            .collect();
+        for writer in writers {
+            assert!(writer.join().unwrap().is_ok());
+        }
+        let leftovers = std::fs::read_dir(dir.path()).unwrap().count();
+        assert_eq!(leftovers, 1);
+    }
+
+    #[test]
+    fn test_create_new_refuses_existing_and_resolve_refuses_escape() {
+        let (dir, path) = temp_file("create", "existing");
+        let err = create_new(&path, b"other").unwrap_err();
+        assert!(matches!(err, WriteError::AlreadyExists { .. }));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "existing");
+
+        let root = dir.path();
+        assert!(resolve_in_workspace(root, "../outside.md").is_err());
+        assert!(resolve_in_workspace(root, "/etc/passwd").is_err());
+        let inside = resolve_in_workspace(root, "new/skill.md").unwrap();
+        assert!(inside.ends_with("new/skill.md"));
+    }
+
+    #[test]
+    fn test_check_expectation_detects_outside_edit() {
+        let (_dir, path) = temp_file("conflict", "loaded");
+        let expected = WriteExpectation {
+            hash: Some(content_hash(b"loaded")),
+            mtime: None,
+        };
+        assert!(check_expectation(&path, &expected).is_ok());
+
+        std::fs::write(&path, "edited elsewhere").unwrap();
+        let err = check_expectation(&path, &expected).unwrap_err();
+        assert!(matches!(err, WriteError::Conflict { .. }));
+    }
+}
@@ -0,0 +1,66 @@
+//! test_support.rs — Fixtures shared by the unit tests of several modules.
+//!
+//! Skill fixtures carry only what the graph and query code look at; scratch
+//! directories are created fresh per test and removed when dropped.
+
+use std::path::{Path, PathBuf};
+
+use crate::fs_scan::{SkillEntry, SkillIndex};
+
+/// Scanned skill at `path` with the given name and frontmatter, empty body.
+pub fn skill_entry(path: &str, name: &str, frontmatter: serde_json::Value) -> SkillEntry {
+    SkillEntry {
+        path: path.to_string(),
+        name: name.to_string(),
+        frontmatter: Some(frontmatter),
+        frontmatter_format: None,
+        body: String::new(),
+        bundle: None,
+        content_hash: None,
+        mtime: None,
+        document: None,
+    }
+}
+
+/// Scanned skill named `name` at `/ws/<name>.md`.
+pub fn skill(name: &str, frontmatter: serde_json::Value) -> SkillEntry {
+    skill_entry(&format!("/ws/{}.md", name), name, frontmatter)
+}
+
+/// Index entry at `path`, named by its frontmatter `name` if it has one.
+pub fn skill_index(path: &str, frontmatter: serde_json::Value) -> SkillIndex {
+    SkillIndex {
+        path: path.to_string(),
+        name: frontmatter["name"].as_str().unwrap_or("").to_string(),
+        frontmatter: Some(frontmatter),
+        frontmatter_format: None,
+        bundle: None,
+    }
+}
+
+/// Empty, canonical directory under the system temp dir, removed on drop.
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    /// Fresh `fv-<label>-<pid>` directory; labels keep parallel tests apart.
+    pub fn new(label: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("fv-{}-{}", label, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        Self(dir.canonicalize().unwrap())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+
+    pub fn join(&self, relative: impl AsRef<Path>) -> PathBuf {
+        self.0.join(relative)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
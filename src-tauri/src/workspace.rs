@@ -0,0 +1,173 @@
+//! workspace.rs — Registry of opened workspace roots and path sandboxing.
+//!
+//! The webview can only reach files through commands, and every command that
+//! takes a path checks it here first: the path is canonicalized (resolving
+//! `..` and symlinks) and must land inside a root registered by
+//! `open_workspace`. Roots only come from a native folder dialog run by the
+//! backend, never from a path the webview sends, so a compromised frontend
+//! cannot read, write or diff anything outside the folders the user opened.
+//! Git commands follow the same rule: a repository that merely contains a
+//! workspace is outside it, so its history is not readable from here.
+//!
+//! Tauri commands exposed:
+//!   - `open_workspace`  → ask the user for a folder and register it,
+//!     returns its canonical root, or null when the dialog was cancelled
+//!   - `close_workspace` → unregister it again
+
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, State};
+use tauri_plugin_dialog::DialogExt;
+
+// ---------------------------------------------------------------------------
+// Managed state
+// ---------------------------------------------------------------------------
+
+/// Canonical roots of the workspaces opened in this session.
+#[derive(Default)]
+pub struct WorkspaceRegistry {
+    roots: Mutex<Vec<PathBuf>>,
+}
+
+impl WorkspaceRegistry {
+    fn roots(&self) -> Result<Vec<PathBuf>, String> {
+        self.roots
+            .lock()
+            .map(|roots| roots.clone())
+            .map_err(|_| "workspace registry lock poisoned".to_string())
+    }
+
+    fn register(&self, path: &Path) -> Result<PathBuf, String> {
+        let root = std::fs::canonicalize(path)
+            .map_err(|e| format!("Workspace not found: {}: {}", path.display(), e))?;
+        if !root.is_dir() {
+            return Err(format!("Not a directory: {}", path.display()));
+        }
+
+        let mut roots = self
+            .roots
+            .lock()
+            .map_err(|_| "workspace registry lock poisoned".to_string())?;
+        if !roots.contains(&root) {
+            roots.push(root.clone());
+        }
+        Ok(root)
+    }
+
+    /// Register the folder `pick` returns; `None` when nothing was picked.
+    /// `pick` is the native dialog outside tests, so only the user chooses.
+    pub fn open_picked(
+        &self,
+        pick: impl FnOnce() -> Option<PathBuf>,
+    ) -> Result<Option<PathBuf>, String> {
+        match pick() {
+            Some(folder) => self.register(&folder).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    pub fn unregister(&self, path: &Path) -> Result<bool, String> {
+        let root = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        let mut roots = self
+            .roots
+            .lock()
+            .map_err(|_| "workspace registry lock poisoned".to_string())?;
+        let before = roots.len();
+        roots.retain(|r| *r != root);
+        Ok(roots.len() != before)
+    }
+
+    fn check_inside(&self, canonical: PathBuf, shown: &str) -> Result<PathBuf, String> {
+        if self.roots()?.iter().any(|root| canonical.starts_with(root)) {
+            Ok(canonical)
+        } else {
+            Err(format!("Path is outside the open workspace: {}", shown))
+        }
+    }
+
+    /// Canonicalize an existing `path` and require it to be inside a
+    /// registered root.
+    pub fn resolve(&self, path: &str) -> Result<PathBuf, String> {
+        let canonical =
+            std::fs::canonicalize(path).map_err(|e| format!("Not found: {}: {}", path, e))?;
+        self.check_inside(canonical, path)
+    }
+}
+
+/// Pathspecs handed to git must stay relative to the repository.
+pub fn check_relative_pathspec(pathspec: &str) -> Result<(), String> {
+    let inside = Path::new(pathspec)
+        .components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+    if inside && !pathspec.starts_with(':') {
+        Ok(())
+    } else {
+        Err(format!("Invalid path filter: {}", pathspec))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Let the user pick a folder in a native dialog and register it as an open
+/// workspace. Every file and git command rejects paths outside the
+/// registered roots. Returns `None` when the dialog is cancelled.
+#[tauri::command]
+pub async fn open_workspace(
+    app: AppHandle,
+    workspaces: State<'_, WorkspaceRegistry>,
+) -> Result<Option<String>, String> {
+    let root = workspaces.open_picked(|| {
+        app.dialog()
+            .file()
+            .set_title("Open skills folder")
+            .blocking_pick_folder()
+            .and_then(|picked| picked.into_path().ok())
+    })?;
+    Ok(root.map(|root| root.to_string_lossy().to_string()))
+}
+
+/// Unregister a workspace. Returns false when it was not open.
+#[tauri::command]
+pub async fn close_workspace(
+    workspaces: State<'_, WorkspaceRegistry>,
+    folder_path: String,
+) -> Result<bool, String> {
+    workspaces.unregister(Path::new(&folder_path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    #[test]
+    fn test_registry_rejects_paths_outside_roots() {
+        let base = TempDir::new("workspace");
+        let vault = base.join("repo/vault");
+        std::fs::create_dir_all(&vault).unwrap();
+        std::fs::create_dir_all(base.join("repo/.git")).unwrap();
+        std::fs::write(vault.join("a.md"), "---\nname: a\n---\n").unwrap();
+        std::fs::write(base.join("secret.md"), "").unwrap();
+
+        let registry = WorkspaceRegistry::default();
+        let vault_str = vault.to_string_lossy().to_string();
+        assert!(registry.resolve(&format!("{}/a.md", vault_str)).is_err());
+
+        // A cancelled dialog registers nothing, whatever the webview wanted
+        assert_eq!(registry.open_picked(|| None).unwrap(), None);
+        assert!(registry.resolve(&format!("{}/a.md", vault_str)).is_err());
+
+        registry.open_picked(|| Some(vault.clone())).unwrap();
+        assert!(registry.resolve(&format!("{}/a.md", vault_str)).is_ok());
+        assert!(registry
+            .resolve(&format!("{}/../../secret.md", vault_str))
+            .is_err());
+        // The repository containing the workspace is outside it too
+        assert!(registry
+            .resolve(&base.join("repo").to_string_lossy())
+            .is_err());
+        assert!(check_relative_pathspec("../etc").is_err());
+    }
+}
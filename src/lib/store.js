@@ -5,12 +5,19 @@ import { invoke } from '@tauri-apps/api/core';
  * @typedef {'graph' | 'files' | 'git' | 'settings'} TabId
  */
 
+let activeScanId = null;
+let scanCounter = 0;
+let openWorkspaceRoot = null;
+let searchCounter = 0;
+
 function createStore() {
   const { subscribe, update, set } = writable({
     appName: 'FV Skills Command',
     skills: [],
     selectedSkillId: null,
     searchQuery: '',
+    /** Ranked `search_skills` hits for `searchQuery`; null when not searching. */
+    searchHits: null,
     folderPath: null,
     loading: false,
     typeFilters: new Set(),
@@ -24,24 +31,33 @@ function createStore() {
     set,
 
     async openFolder() {
-      let previousPath = '';
-      update((s) => {
-        previousPath = s.folderPath || '';
-        return { ...s, loading: true };
-      });
+      update((s) => ({ ...s, loading: true }));
 
       try {
-        if (typeof window === 'undefined' || typeof window.prompt !== 'function') {
-          throw new Error('Folder picker is unavailable in this runtime.');
-        }
-
-        const folderPath = window.prompt('Enter the absolute path to your skills folder:', previousPath || '')?.trim();
+        // The backend shows the folder dialog and registers the chosen root;
+        // file and git commands only accept paths inside registered workspaces.
+        const folderPath = await invoke('open_workspace');
         if (!folderPath) {
           update((s) => ({ ...s, loading: false }));
           return;
         }
+        if (openWorkspaceRoot && openWorkspaceRoot !== folderPath) {
+          invoke('close_workspace', { folderPath: openWorkspaceRoot }).catch(() => {});
+        }
+        openWorkspaceRoot = folderPath;
+
+        // Stop any scan still running for a previously chosen folder.
+        if (activeScanId) {
+          invoke('cancel_scan', { scanId: activeScanId }).catch(() => {});
+        }
+        const scanId = `scan-${++scanCounter}`;
+        activeScanId = scanId;
 
-        const scanResult = await invoke('scan_folder', { folderPath });
+        const scanResult = await invoke('scan_folder', { folderPath, scanId });
+        if (activeScanId !== scanId || scanResult?.cancelled) {
+          return;
+        }
+        activeScanId = null;
         const scannedSkills = Array.isArray(scanResult?.skills) ? scanResult.skills : [];
         const normalizedSkills = scannedSkills
           .map((skill) => normalizeSkill(skill, folderPath))
@@ -81,8 +97,30 @@ function createStore() {
       });
     },
 
-    setSearchQuery(query) {
-      update((s) => ({ ...s, searchQuery: query }));
+    async setSearchQuery(query) {
+      let folderPath = null;
+      update((s) => {
+        folderPath = s.folderPath;
+        return { ...s, searchQuery: query };
+      });
+
+      const search = ++searchCounter;
+      if (!folderPath || !query.trim()) {
+        update((s) => ({ ...s, searchHits: null }));
+        return;
+      }
+
+      try {
+        const result = await invoke('search_skills', { workspaceRoot: folderPath, query });
+        if (search === searchCounter) {
+          update((s) => ({ ...s, searchHits: result?.hits || [] }));
+        }
+      } catch (error) {
+        console.error('[store.setSearchQuery] search failed:', error);
+        if (search === searchCounter) {
+          update((s) => ({ ...s, searchHits: null }));
+        }
+      }
     },
 
     toggleTypeFilter(type) {
@@ -149,6 +187,8 @@ function normalizeSkill(entry, folderPath) {
     status: typeof frontmatter.status === 'string' ? frontmatter.status : null,
     frontmatter,
     body: typeof entry?.body === 'string' ? entry.body : '',
+    contentHash: typeof entry?.content_hash === 'string' ? entry.content_hash : null,
+    mtime: typeof entry?.mtime === 'number' ? entry.mtime : null,
   };
 }
 